@@ -1,4 +1,6 @@
+use crate::commands::run_blocking;
 use crate::context::schema::AppResult;
+use crate::utils::db_utils;
 use tauri::AppHandle;
 
 #[tauri::command]
@@ -13,3 +15,16 @@ pub async fn restart_app(app_handle: AppHandle) -> AppResult<()> {
     // 但我们需要返回Ok(())来满足函数签名
     Ok(())
 }
+
+#[tauri::command]
+pub async fn get_history_enabled(app_handle: AppHandle) -> AppResult<bool> {
+    run_blocking(move || db_utils::history_enabled(&app_handle)).await
+}
+
+#[tauri::command]
+pub async fn set_history_enabled(app_handle: AppHandle, enabled: bool) -> AppResult<()> {
+    run_blocking(move || {
+        db_utils::set_setting(&app_handle, db_utils::HISTORY_ENABLED, &enabled.to_string())
+    })
+    .await
+}
@@ -0,0 +1,133 @@
+use crate::commands::run_blocking;
+use crate::context::context::{DEFAULT_MYSQL_SSLMODE, DEFAULT_POSTGRES_SSLMODE};
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use crate::utils::connection_utils::{
+    connection_kind, delete_connection_string, load_connection_string, mask_password,
+    store_connection_string, validate_connection_name,
+};
+use crate::utils::db_utils;
+use chrono::Local;
+use datafusion_table_providers::sql::db_connection_pool::{
+    mysqlpool::MySQLConnectionPool, postgrespool::PostgresConnectionPool,
+};
+use datafusion_table_providers::util::secrets::to_secret_map;
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{command, AppHandle};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+pub struct SavedConnection {
+    pub name: String,
+    pub kind: String,
+    /// Connection string with the password masked
+    pub conn: String,
+    pub created_at: String,
+}
+
+#[command]
+pub async fn save_connection(app: AppHandle, name: String, conn: String) -> AppResult<()> {
+    run_blocking(move || {
+        validate_connection_name(&name)?;
+        let kind = connection_kind(&conn)?;
+
+        store_connection_string(&name, &conn)?;
+        db_utils::conn(&app)?.execute(
+            r#"
+                insert into saved_connections ( name, kind, display_conn, created_at )
+                values (?1, ?2, ?3, ?4)
+                on conflict (name) do update set kind = excluded.kind, display_conn = excluded.display_conn
+                "#,
+            params![
+                name,
+                kind,
+                mask_password(&conn),
+                Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+            ],
+        )?;
+        Ok(())
+    })
+    .await
+}
+
+#[command]
+pub async fn list_connections(app: AppHandle) -> AppResult<Vec<SavedConnection>> {
+    run_blocking(move || {
+        let conn = db_utils::conn(&app)?;
+        let mut stmt = conn.prepare(
+            "select name, kind, display_conn, created_at from saved_connections order by name",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(SavedConnection {
+                name: row.get(0)?,
+                kind: row.get(1)?,
+                conn: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    })
+    .await
+}
+
+#[command]
+pub async fn delete_connection(app: AppHandle, name: String) -> AppResult<()> {
+    run_blocking(move || {
+        delete_connection_string(&name)?;
+        db_utils::conn(&app)?.execute("delete from saved_connections where name = ?1", [name])?;
+        Ok(())
+    })
+    .await
+}
+
+/// Opens a connection either to a saved connection, by `name`, or to an unsaved
+/// connection string, so the settings form can be checked before saving it.
+#[command]
+pub async fn test_connection(name: Option<String>, conn: Option<String>) -> AppResult<()> {
+    let conn = match (conn, name) {
+        (Some(conn), _) => conn,
+        (None, Some(name)) => run_blocking(move || load_connection_string(&name)).await?,
+        (None, None) => {
+            return Err(AppError::BadRequest {
+                message: "Either 'name' or 'conn' is required".to_string(),
+            })
+        }
+    };
+
+    let connect = async {
+        // Same SSL defaults as read_mysql and read_postgres
+        match connection_kind(&conn)? {
+            "mysql" => {
+                MySQLConnectionPool::new(to_secret_map(HashMap::from([
+                    ("connection_string".to_string(), conn.clone()),
+                    ("sslmode".to_string(), DEFAULT_MYSQL_SSLMODE.to_string()),
+                ])))
+                .await?;
+            }
+            _ => {
+                PostgresConnectionPool::new(to_secret_map(HashMap::from([
+                    ("connection_string".to_string(), conn.clone()),
+                    ("sslmode".to_string(), DEFAULT_POSTGRES_SSLMODE.to_string()),
+                ])))
+                .await?;
+            }
+        }
+        Ok::<(), AppError>(())
+    };
+
+    tokio::time::timeout(TEST_TIMEOUT, connect)
+        .await
+        .map_err(|_| AppError::BadRequest {
+            message: format!("Timed out after {}s", TEST_TIMEOUT.as_secs()),
+        })?
+}
@@ -1,10 +1,15 @@
 use std::future::Future;
 use crate::context::error::AppError;
+use crate::context::running::RunningQueries;
 use crate::context::schema::AppResult;
+use futures::future::{abortable, Aborted};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::task;
 
 pub mod app;
 pub mod ai;
+pub mod connection;
 pub mod files;
 pub mod query;
 pub mod utils;
@@ -35,3 +40,48 @@ where
             message: e.to_string(),
         })?
 }
+
+/// Event emitted when a cancellable task starts, carrying the id `cancel_query` takes
+pub const QUERY_STARTED_EVENT: &str = "query_started";
+
+#[derive(Clone, Serialize)]
+pub struct QueryStarted {
+    pub query_id: String,
+}
+
+/// Like `run_blocking_async`, but registers the task under `query_id` so that
+/// `cancel_query` can abort it while it runs. Without an id one is generated, and
+/// either way it is announced with `QUERY_STARTED_EVENT`.
+pub async fn run_cancellable_async<F, Fut, T>(
+    app: &AppHandle,
+    query_id: Option<String>,
+    f: F,
+) -> AppResult<T>
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = AppResult<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    // The handle is registered before the task is spawned, so a rejected id never runs
+    let (future, abort_handle) = abortable(async move { f().await });
+
+    let running = app.state::<RunningQueries>();
+    let query_id = running.insert(query_id, abort_handle)?;
+    let _ = app.emit(
+        QUERY_STARTED_EVENT,
+        QueryStarted {
+            query_id: query_id.clone(),
+        },
+    );
+
+    let result = task::spawn(future).await;
+    running.remove(&query_id);
+
+    match result {
+        Ok(Ok(result)) => result,
+        Ok(Err(Aborted)) => Err(AppError::Cancelled { query_id }),
+        Err(err) => Err(AppError::InternalServer {
+            message: err.to_string(),
+        }),
+    }
+}
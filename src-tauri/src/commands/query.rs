@@ -1,22 +1,40 @@
-use crate::commands::{run_blocking, run_blocking_async};
-use crate::context::context::{collect, get_data_frame, get_sql_context, register};
+use crate::commands::{run_blocking, run_blocking_async, run_cancellable_async};
+use crate::context::context::{
+    collect_script, get_script_data_frame, mysql_pool, DEFAULT_CONNECT_TIMEOUT,
+    DEFAULT_MYSQL_SSLMODE, SOURCE_TABLE_PREFIX,
+};
+use crate::context::cursor::{QueryCursor, QueryCursors};
 use crate::context::error::AppError;
+use crate::context::running::RunningQueries;
 use crate::context::schema::AppResult;
-use crate::sql::generator::{generate_sql_inserts, generate_sql_update};
+use crate::context::session::QuerySession;
+use crate::sql::ddl::generate_create_table;
+use crate::sql::dialect::{dialect_from_str, MySqlDialect, SqlDialect};
+use crate::sql::generator::{
+    generate_sql_deletes, generate_sql_inserts, generate_sql_update, generate_sql_upserts,
+    UpdateOptions,
+};
+use crate::utils::connection_utils::{connection_kind, load_connection_string};
 use crate::utils::date_utils::time_difference_from_now;
 use crate::utils::db_utils;
 use crate::utils::db_utils::insert_query_history;
+use crate::writer::csv::{write_csv_crlf, CsvExportOptions};
+use crate::writer::json::{write_json_array, JsonExportOptions};
+use crate::writer::mysql::{write_mysql, MySqlExportOptions};
+use crate::writer::xlsx::{XlsxExportOptions, XlsxWriter};
+use crate::writer::{ExportTarget, WriteMode};
 use chrono::Utc;
 use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
-use datafusion::config::CsvOptions;
-use datafusion::dataframe::DataFrameWriteOptions;
-use dirs;
-use serde::Serialize;
+use datafusion::config::TableParquetOptions;
+use datafusion::logical_expr::TableType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
-use tauri::{command, AppHandle};
+use std::io::{BufWriter, Write};
+use tauri::{command, AppHandle, Emitter, Manager};
 
 #[derive(Serialize)]
 pub struct FetchResult {
@@ -25,6 +43,21 @@ pub struct FetchResult {
     pub query_time: String,
 }
 
+#[derive(Serialize)]
+pub struct OpenQueryResult {
+    pub handle: u64,
+    pub header: Vec<String>,
+    pub total_rows: Option<usize>,
+    pub query_time: String,
+}
+
+#[derive(Serialize)]
+pub struct PageResult {
+    pub rows: Vec<Vec<String>>,
+    pub done: bool,
+    pub query_time: String,
+}
+
 #[derive(Serialize)]
 pub struct FetchHistory {
     pub sql: String,
@@ -32,30 +65,169 @@ pub struct FetchHistory {
     pub created_at: String,
 }
 
+#[derive(Serialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub table_type: String,
+    pub columns: Vec<String>,
+}
+
 #[derive(Serialize)]
 pub struct WriterResult {
     pub query_time: String,
     pub file_name: String,
 }
 
-pub enum Dialect {
-    MySQL,
-    PostgreSQL,
+#[derive(Serialize)]
+pub struct ExportDatabaseResult {
+    pub query_time: String,
+    pub rows_written: usize,
 }
 
-impl Dialect {
-    fn from_str(s: &str) -> AppResult<Self> {
-        match s {
-            "MySQL" => Ok(Dialect::MySQL),
-            "PostgreSQL" => Ok(Dialect::PostgreSQL),
-            _ => Err(AppError::BadRequest {
-                message: format!(
-                    "Invalid dialect: '{}'. Please use 'MySQL' or 'PostgreSQL'.",
-                    s
-                ),
-            }),
+/// Options of one `writer` format. Each format deserializes only its own fields, so
+/// an option meant for another format (e.g. a `delimiter` for Parquet) is rejected
+/// instead of being dropped.
+enum ExportOptions {
+    /// csv and tsv
+    Csv(CsvExportOptions),
+    Parquet(ParquetExportOptions),
+    /// json and ndjson
+    Json(JsonExportOptions),
+    Xlsx(XlsxExportOptions),
+    Sql(SqlExportOptions),
+}
+
+impl ExportOptions {
+    fn parse(file_extension: &str, options: Option<Value>) -> AppResult<Self> {
+        let options = options.unwrap_or_else(|| Value::Object(Default::default()));
+        let parsed = match file_extension {
+            "csv" | "tsv" => serde_json::from_value(options).map(ExportOptions::Csv),
+            "parquet" => serde_json::from_value(options).map(ExportOptions::Parquet),
+            "ndjson" | "json" => serde_json::from_value(options).map(ExportOptions::Json),
+            "xlsx" => serde_json::from_value(options).map(ExportOptions::Xlsx),
+            "sql" => serde_json::from_value(options).map(ExportOptions::Sql),
+            _ => unreachable!(), // Unsupported file types are rejected before parsing
+        };
+        parsed.map_err(|err| AppError::BadRequest {
+            message: format!("Invalid {} export options: {}", file_extension, err),
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ParquetExportOptions {
+    pub compression: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SqlExportOptions {
+    pub table_name: Option<String>,
+    pub statement_type: Option<String>,
+    /// Rows per INSERT, also used as the batch size of UPSERT and DELETE statements
+    pub max_values_per_insert: Option<usize>,
+    pub where_column: Option<String>,
+    pub key_columns: Option<Vec<String>>,
+    pub set_columns: Option<Vec<String>>,
+    pub exclude_columns: Option<Vec<String>>,
+    pub skip_nulls: Option<bool>,
+    pub use_transaction: Option<bool>,
+    pub create_table: Option<bool>,
+    pub dialect: Option<String>,
+}
+
+impl SqlExportOptions {
+    pub fn statement_type(&self) -> String {
+        self.statement_type
+            .as_ref()
+            .map(|s| s.to_uppercase())
+            .unwrap_or_else(|| "INSERT".to_string())
+    }
+
+    pub fn dialect(&self) -> AppResult<Box<dyn SqlDialect>> {
+        match &self.dialect {
+            Some(dialect) => dialect_from_str(dialect),
+            None => Ok(Box::new(MySqlDialect)),
         }
     }
+
+    /// Composite keys take precedence over the single WHERE column
+    pub fn key_columns(&self) -> Vec<String> {
+        match &self.key_columns {
+            Some(key_columns) if !key_columns.is_empty() => key_columns.clone(),
+            _ => self.where_column.iter().cloned().collect(),
+        }
+    }
+
+    /// Checks the options before the output file is created or truncated
+    pub fn validate(&self) -> AppResult<()> {
+        if self.table_name.is_none() {
+            return Err(AppError::BadRequest {
+                message: "Table name is required for SQL export".to_string(),
+            });
+        }
+
+        let db_dialect = self.dialect()?;
+        let statement_type = self.statement_type();
+        match statement_type.as_str() {
+            "INSERT" => {
+                if self.max_values_per_insert.is_none() {
+                    return Err(AppError::BadRequest {
+                        message: "Max values per insert is required for INSERT statements"
+                            .to_string(),
+                    });
+                }
+            }
+            "UPDATE" | "UPSERT" | "MERGE" | "DELETE" => {
+                if self.key_columns().is_empty() {
+                    return Err(AppError::BadRequest {
+                        message: format!(
+                            "WHERE column or key columns are required for {} statements",
+                            statement_type
+                        ),
+                    });
+                }
+                if matches!(statement_type.as_str(), "UPSERT" | "MERGE")
+                    && !db_dialect.supports_upsert()
+                {
+                    return Err(AppError::BadRequest {
+                        message: format!(
+                            "UPSERT scripts are not supported for {}",
+                            db_dialect.name()
+                        ),
+                    });
+                }
+            }
+            _ => {
+                return Err(AppError::BadRequest {
+                    message: concat!(
+                        "Invalid SQL statement type. ",
+                        "Supported types: INSERT, UPDATE, UPSERT, MERGE, DELETE"
+                    )
+                    .to_string(),
+                });
+            }
+        }
+        if self.use_transaction.unwrap_or(false) && !db_dialect.supports_transactions() {
+            return Err(AppError::BadRequest {
+                message: format!("Transactions are not supported for {}", db_dialect.name()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rows per UPSERT or DELETE statement when no batch size is given
+const DEFAULT_VALUES_PER_STATEMENT: usize = 1000;
+
+/// Event emitted while a SQL export is being streamed to disk
+pub const EXPORT_PROGRESS_EVENT: &str = "export_progress";
+
+#[derive(Clone, Serialize)]
+pub struct ExportProgress {
+    pub query_id: Option<String>,
+    pub rows_written: usize,
 }
 
 #[command]
@@ -64,18 +236,21 @@ pub async fn fetch(
     sql: String,
     offset: usize,
     limit: usize,
+    query_id: Option<String>,
 ) -> AppResult<FetchResult> {
-    run_blocking_async(move || async move {
+    let (task_app, task_sql) = (app.clone(), sql.clone());
+    run_cancellable_async(&task_app, query_id, move || async move {
         let start = Utc::now();
-        let mut context = get_sql_context();
 
-        let new_sql = register(&mut context, &sql, Some(limit), Some(offset))
+        let (mut context, statements) = app
+            .state::<QuerySession>()
+            .register(&sql, Some(limit), Some(offset))
             .await
             .map_err(|err| {
                 let _ = insert_query_history(&app, &sql, "fail");
                 err
             })?;
-        let records = collect(&mut context, &new_sql).await.map_err(|err| {
+        let records = collect_script(&mut context, &statements).await.map_err(|err| {
             let _ = insert_query_history(&app, &sql, "fail");
             err
         })?;
@@ -89,48 +264,138 @@ pub async fn fetch(
             });
         }
 
-        let row = &records[0];
-        let width = row.columns().len();
-
-        let header: Vec<String> = row
+        let header: Vec<String> = records[0]
             .schema()
             .fields()
             .iter()
             .map(|c| c.name().to_string())
             .collect();
+        let rows = format_rows(&records)?;
 
-        // Pre-calculate the total number of rows to avoid frequent reallocation
-        let total_rows: usize = records.iter().map(|r| r.num_rows()).sum();
-        let mut rows: Vec<Vec<String>> = Vec::with_capacity(total_rows);
-        let options = FormatOptions::default().with_null("NULL");
-
-        for record in records {
-            let formatters = record
-                .columns()
-                .iter()
-                .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
-                .collect::<Result<Vec<_>, ArrowError>>()?;
-
-            for row in 0..record.num_rows() {
-                let mut cells = Vec::with_capacity(width);
-                for (_, formatter) in formatters.iter().enumerate() {
-                    cells.push(formatter.value(row).to_string());
-                }
-                rows.push(cells);
+        insert_query_history(&app, &sql, "successful")?;
+
+        Ok(FetchResult {
+            header,
+            rows,
+            query_time: time_difference_from_now(start),
+        })
+    })
+    .await
+    .map_err(|err| record_cancelled(&task_app, &task_sql, err))
+}
+
+/// Aborted tasks never reach their own history insert, so cancellations are recorded here
+fn record_cancelled(app: &AppHandle, sql: &str, err: AppError) -> AppError {
+    if let AppError::Cancelled { .. } = err {
+        let _ = insert_query_history(app, sql, "cancelled");
+    }
+    err
+}
+
+/// Formats record batches into display strings, one `Vec` per row
+fn format_rows(records: &[RecordBatch]) -> AppResult<Vec<Vec<String>>> {
+    // Pre-calculate the total number of rows to avoid frequent reallocation
+    let total_rows: usize = records.iter().map(|r| r.num_rows()).sum();
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(total_rows);
+    let options = FormatOptions::default().with_null("NULL");
+
+    for record in records {
+        let width = record.columns().len();
+        let formatters = record
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+
+        for row in 0..record.num_rows() {
+            let mut cells = Vec::with_capacity(width);
+            for formatter in formatters.iter() {
+                cells.push(formatter.value(row).to_string());
             }
+            rows.push(cells);
         }
+    }
+
+    Ok(rows)
+}
+
+#[command]
+pub async fn open_query(
+    app: AppHandle,
+    sql: String,
+    query_id: Option<String>,
+) -> AppResult<OpenQueryResult> {
+    let (task_app, task_sql) = (app.clone(), sql.clone());
+    run_cancellable_async(&task_app, query_id, move || async move {
+        let start = Utc::now();
+
+        let cursor = async {
+            let (mut context, statements) = app
+                .state::<QuerySession>()
+                .register(&sql, None, None)
+                .await?;
+            let df = get_script_data_frame(&mut context, &statements).await?;
+            QueryCursor::open(df).await
+        }
+        .await
+        .map_err(|err| {
+            let _ = insert_query_history(&app, &sql, "fail");
+            err
+        })?;
+
+        let header: Vec<String> = cursor
+            .schema()
+            .fields()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let total_rows = cursor.total_rows();
+        let handle = app.state::<QueryCursors>().insert(cursor).await;
 
         insert_query_history(&app, &sql, "successful")?;
 
-        Ok(FetchResult {
+        Ok(OpenQueryResult {
+            handle,
             header,
-            rows,
+            total_rows,
+            query_time: time_difference_from_now(start),
+        })
+    })
+    .await
+    .map_err(|err| record_cancelled(&task_app, &task_sql, err))
+}
+
+#[command]
+pub async fn fetch_page(app: AppHandle, handle: u64, size: usize) -> AppResult<PageResult> {
+    run_blocking_async(move || async move {
+        let start = Utc::now();
+        let cursors = app.state::<QueryCursors>();
+        let cursor = cursors.get(handle).await?;
+
+        let (records, done) = {
+            let mut cursor = cursor.lock().await;
+            let records = cursor.next_page(size.max(1)).await?;
+            (records, cursor.is_exhausted())
+        };
+        if done {
+            cursors.remove(handle).await;
+        }
+
+        Ok(PageResult {
+            rows: format_rows(&records)?,
+            done,
             query_time: time_difference_from_now(start),
         })
     })
     .await
 }
 
+#[command]
+pub async fn close_query(app: AppHandle, handle: u64) -> AppResult<()> {
+    app.state::<QueryCursors>().remove(handle).await;
+    Ok(())
+}
+
 #[command]
 pub async fn sql_history(app: AppHandle) -> AppResult<Vec<FetchHistory>> {
     run_blocking(move || {
@@ -157,134 +422,482 @@ pub async fn sql_history(app: AppHandle) -> AppResult<Vec<FetchHistory>> {
 }
 
 #[command]
-pub async fn writer(
-    _app: AppHandle,
-    file_type: String,
-    sql: String,
-    table_name: Option<String>,
-    max_values_per_insert: Option<usize>,
-    sql_statement_type: Option<String>,
-    where_column: Option<String>,
-    dialect: Option<String>,
-) -> AppResult<WriterResult> {
+pub async fn cancel_query(app: AppHandle, query_id: String) -> AppResult<bool> {
+    Ok(app.state::<RunningQueries>().cancel(&query_id))
+}
+
+#[command]
+pub async fn list_tables(app: AppHandle) -> AppResult<Vec<TableInfo>> {
     run_blocking_async(move || async move {
-        let mut downloads_dir = dirs::download_dir().ok_or_else(|| AppError::BadRequest {
-            message: "Couldn't find the current working directory".to_string(),
-        })?;
+        let context = app.state::<QuerySession>().context().await;
+        let state = context.state();
+        let options = &state.config_options().catalog;
 
-        let db_dialect = match dialect {
-            Some(dialect) => Dialect::from_str(&dialect)?,
-            None => Dialect::MySQL,
-        };
+        let schema = context
+            .catalog(&options.default_catalog)
+            .and_then(|catalog| catalog.schema(&options.default_schema))
+            .ok_or_else(|| AppError::InternalServer {
+                message: "Default schema not found".to_string(),
+            })?;
 
-        let start = Utc::now();
+        let mut table_names = schema.table_names();
+        table_names.sort();
 
-        // Validate required parameters for SQL export
-        if file_type.to_lowercase() == "sql" {
-            if table_name.is_none() {
-                return Err(AppError::BadRequest {
-                    message: "Table name is required for SQL export".to_string(),
-                });
+        let mut tables = Vec::with_capacity(table_names.len());
+        for name in table_names {
+            // Tables backing read_* functions are an implementation detail
+            if name.starts_with(SOURCE_TABLE_PREFIX) {
+                continue;
             }
-
-            let statement_type = sql_statement_type
-                .as_ref()
-                .map(|s| s.to_uppercase())
-                .unwrap_or_else(|| "INSERT".to_string());
-            match statement_type.as_str() {
-                "INSERT" => {
-                    if max_values_per_insert.is_none() {
-                        return Err(AppError::BadRequest {
-                            message: "Max values per insert is required for INSERT statements"
-                                .to_string(),
-                        });
-                    }
-                }
-                "UPDATE" => {
-                    if where_column.is_none() {
-                        return Err(AppError::BadRequest {
-                            message: "WHERE column is required for UPDATE statements".to_string(),
-                        });
+            if let Some(table) = schema.table(&name).await? {
+                tables.push(TableInfo {
+                    table_type: match table.table_type() {
+                        TableType::Base => "TABLE",
+                        TableType::View => "VIEW",
+                        TableType::Temporary => "TEMPORARY",
                     }
-                }
-                _ => {
-                    return Err(AppError::BadRequest {
-                        message: "Invalid SQL statement type. Supported types: INSERT, UPDATE"
-                            .to_string(),
-                    });
-                }
+                    .to_string(),
+                    columns: table
+                        .schema()
+                        .fields()
+                        .iter()
+                        .map(|f| f.name().to_string())
+                        .collect(),
+                    name,
+                });
             }
         }
 
-        let mut context = get_sql_context();
-        let new_sql = register(&mut context, &sql, None, None).await?;
-        let df = get_data_frame(&mut context, &new_sql).await?;
+        Ok(tables)
+    })
+    .await
+}
+
+#[command]
+pub async fn generate_ddl(
+    app: AppHandle,
+    sql: String,
+    dialect: Option<String>,
+    table_name: Option<String>,
+) -> AppResult<String> {
+    run_blocking_async(move || async move {
+        let db_dialect: Box<dyn SqlDialect> = match dialect {
+            Some(dialect) => dialect_from_str(&dialect)?,
+            None => Box::new(MySqlDialect),
+        };
+        let table_name = table_name.unwrap_or_else(|| "query_result".to_string());
+
+        let (mut context, statements) = app
+            .state::<QuerySession>()
+            .register(&sql, None, None)
+            .await?;
+        let df = get_script_data_frame(&mut context, &statements).await?;
+
+        let mut ddl = Vec::new();
+        generate_create_table(&df, &table_name, db_dialect.as_ref(), &mut ddl).await?;
+        Ok(String::from_utf8_lossy(&ddl).into_owned())
+    })
+    .await
+}
+
+#[command]
+pub async fn drop_table(app: AppHandle, name: String) -> AppResult<()> {
+    run_blocking_async(move || async move {
+        let context = app.state::<QuerySession>().context().await;
+        match context.deregister_table(name.as_str())? {
+            Some(_) => Ok(()),
+            None => Err(AppError::BadRequest {
+                message: format!("Table '{}' not found", name),
+            }),
+        }
+    })
+    .await
+}
+
+#[command]
+pub async fn writer(
+    app: AppHandle,
+    file_type: String,
+    sql: String,
+    options: Option<Value>,
+    output_path: Option<String>,
+    write_mode: Option<String>,
+    partition_by: Option<Vec<String>>,
+    query_id: Option<String>,
+) -> AppResult<WriterResult> {
+    let task_app = app.clone();
+    let progress_id = query_id.clone();
+    run_cancellable_async(&task_app, query_id, move || async move {
+        let write_mode = match write_mode {
+            Some(write_mode) => WriteMode::from_str(&write_mode)?,
+            None => WriteMode::Overwrite,
+        };
+        let partition_by = partition_by.unwrap_or_default();
+
+        let start = Utc::now();
 
         // Determine file extension
         let file_extension = match file_type.to_lowercase().as_str() {
             "csv" => "csv",
             "tsv" => "tsv",
             "sql" => "sql",
+            "parquet" => "parquet",
+            "ndjson" => "ndjson",
+            "json" => "json",
+            "xlsx" => "xlsx",
             _ => {
                 return Err(AppError::BadRequest {
-                    message: "Unsupported file type. Supported types: csv, tsv, sql".to_string(),
+                    message: concat!(
+                        "Unsupported file type. ",
+                        "Supported types: csv, tsv, sql, parquet, ndjson, json, xlsx"
+                    )
+                    .to_string(),
                 })
             }
         };
+        let options = ExportOptions::parse(file_extension, options)?;
 
-        downloads_dir.push(format!(
-            "easydb_{}.{}",
-            Utc::now().format("%Y%m%d%H%M%S").to_string(),
-            file_extension
-        ));
-        let file_path = downloads_dir.to_string_lossy().to_string();
-
-        match file_type.to_lowercase().as_str() {
-            "csv" => {
-                df.write_csv(&file_path, DataFrameWriteOptions::new(), None)
-                    .await?;
-            }
-            "tsv" => {
-                let mut options = CsvOptions::default();
-                options.delimiter = b'\t';
-                df.write_csv(&file_path, DataFrameWriteOptions::new(), None)
-                    .await?;
+        if let ExportOptions::Sql(sql_options) = &options {
+            sql_options.validate()?;
+        }
+        if !partition_by.is_empty()
+            && !matches!(file_extension, "csv" | "tsv" | "parquet" | "ndjson")
+        {
+            return Err(AppError::BadRequest {
+                message: "partition_by is only supported for csv, tsv, parquet and ndjson"
+                    .to_string(),
+            });
+        }
+        if matches!(write_mode, WriteMode::Append)
+            && partition_by.is_empty()
+            && matches!(file_extension, "parquet" | "json" | "xlsx")
+        {
+            return Err(AppError::BadRequest {
+                message: format!(
+                    "Appending to a single {} file isn't possible, write to a directory instead",
+                    file_extension
+                ),
+            });
+        }
+
+        let (mut context, statements) = app
+            .state::<QuerySession>()
+            .register(&sql, None, None)
+            .await?;
+        let df = get_script_data_frame(&mut context, &statements).await?;
+
+        let compression_suffix = match &options {
+            ExportOptions::Csv(csv_options) => {
+                if csv_options.is_crlf()? && !partition_by.is_empty() {
+                    return Err(AppError::BadRequest {
+                        message: "The \\r\\n line terminator can't be combined with partition_by"
+                            .to_string(),
+                    });
+                }
+                csv_options.extension_suffix()?
             }
-            "sql" => {
-                // Generate SQL statements based on statement type
-                let table_name_value = table_name.unwrap();
-                let statement_type = sql_statement_type
-                    .as_ref()
-                    .map(|s| s.to_uppercase())
-                    .unwrap_or_else(|| "INSERT".to_string());
-
-                let sql_content = match statement_type.as_str() {
-                    "INSERT" => {
-                        let max_values = max_values_per_insert.unwrap();
-                        generate_sql_inserts(df, &table_name_value, max_values, &db_dialect).await?
+            _ => "",
+        };
+
+        let target = ExportTarget::resolve(
+            output_path,
+            format!(
+                "easydb_{}.{}{}",
+                Utc::now().format("%Y%m%d%H%M%S").to_string(),
+                file_extension,
+                compression_suffix
+            ),
+            !partition_by.is_empty(),
+        )?;
+        let appending = target.prepare(&write_mode)?;
+        let file_path = target.write_path(appending);
+        let write_options = target.write_options(partition_by);
+
+        let written: AppResult<()> = async {
+            match options {
+                ExportOptions::Csv(csv_options) => {
+                    let default_delimiter = if file_extension == "tsv" { b'\t' } else { b',' };
+                    let mut options = csv_options.to_csv_options(default_delimiter)?;
+                    // The existing file already starts with a header
+                    if appending && fs::metadata(target.path())?.len() > 0 {
+                        options.has_header = Some(false);
                     }
-                    "UPDATE" => {
-                        let where_column_value = where_column.unwrap();
-                        generate_sql_update(df, &table_name_value, &where_column_value, &db_dialect)
-                            .await?
+                    if csv_options.is_crlf()? {
+                        write_csv_crlf(df, &file_path, &options).await?;
+                    } else {
+                        df.write_csv(&file_path, write_options, Some(options))
+                            .await?;
+                    }
+                }
+                ExportOptions::Parquet(parquet_options) => {
+                    let mut options = TableParquetOptions::default();
+                    if let Some(compression) = parquet_compression(parquet_options.compression)? {
+                        options.global.compression = Some(compression);
+                    }
+                    df.write_parquet(&file_path, write_options, Some(options))
+                        .await?;
+                }
+                ExportOptions::Json(_) if file_extension == "ndjson" => {
+                    df.write_json(&file_path, write_options, None).await?;
+                }
+                ExportOptions::Json(_) => {
+                    write_json_array(df, &file_path).await?;
+                }
+                ExportOptions::Xlsx(xlsx_options) => {
+                    let mut xlsx_writer = XlsxWriter::new(file_path.clone())
+                        .with_style_header(xlsx_options.style_header.unwrap_or(true));
+                    if let Some(sheet_name) = xlsx_options.sheet_name {
+                        xlsx_writer = xlsx_writer.with_sheet_name(sheet_name);
+                    }
+                    xlsx_writer.write(df).await?;
+                }
+                ExportOptions::Sql(sql_options) => {
+                    // Generate SQL statements based on statement type
+                    let db_dialect = sql_options.dialect()?;
+                    let key_columns = sql_options.key_columns();
+                    let table_name_value = sql_options.table_name.unwrap_or_default();
+                    let statement_type = sql_options.statement_type();
+
+                    let mut file = BufWriter::new(File::create(&file_path)?);
+                    if sql_options.create_table.unwrap_or(false) {
+                        generate_create_table(
+                            &df,
+                            &table_name_value,
+                            db_dialect.as_ref(),
+                            &mut file,
+                        )
+                        .await?;
+                        file.write_all(b"\n\n")?;
                     }
-                    _ => {
-                        return Err(AppError::BadRequest {
-                            message: "Invalid SQL statement type".to_string(),
-                        });
+                    let use_transaction = sql_options.use_transaction.unwrap_or(false);
+                    if use_transaction {
+                        if let Some(begin) = db_dialect.begin_transaction() {
+                            writeln!(file, "{}", begin)?;
+                        }
                     }
-                };
+                    let on_progress = |rows_written: usize| {
+                        let _ = app.emit(
+                            EXPORT_PROGRESS_EVENT,
+                            ExportProgress {
+                                query_id: progress_id.clone(),
+                                rows_written,
+                            },
+                        );
+                    };
 
-                let mut file = File::create(&downloads_dir)?;
-                write!(file, "{}", sql_content)?;
+                    match statement_type.as_str() {
+                        "INSERT" => {
+                            let max_values = sql_options.max_values_per_insert.unwrap();
+                            generate_sql_inserts(
+                                df,
+                                &table_name_value,
+                                max_values,
+                                db_dialect.as_ref(),
+                                &mut file,
+                                on_progress,
+                            )
+                            .await?
+                        }
+                        "UPDATE" => {
+                            let mut options = UpdateOptions::new(key_columns)
+                                .with_exclude_columns(
+                                    sql_options.exclude_columns.unwrap_or_default(),
+                                )
+                                .with_skip_nulls(sql_options.skip_nulls.unwrap_or(false));
+                            if let Some(set_columns) = sql_options.set_columns {
+                                options = options.with_set_columns(set_columns);
+                            }
+                            generate_sql_update(
+                                df,
+                                &table_name_value,
+                                &options,
+                                db_dialect.as_ref(),
+                                &mut file,
+                                on_progress,
+                            )
+                            .await?
+                        }
+                        // MERGE is the UPSERT form for SQL Server and Oracle, so both names
+                        // produce the dialect's native statement
+                        "UPSERT" | "MERGE" => {
+                            generate_sql_upserts(
+                                df,
+                                &table_name_value,
+                                &key_columns,
+                                sql_options
+                                    .max_values_per_insert
+                                    .unwrap_or(DEFAULT_VALUES_PER_STATEMENT),
+                                db_dialect.as_ref(),
+                                &mut file,
+                                on_progress,
+                            )
+                            .await?
+                        }
+                        "DELETE" => {
+                            generate_sql_deletes(
+                                df,
+                                &table_name_value,
+                                &key_columns,
+                                sql_options
+                                    .max_values_per_insert
+                                    .unwrap_or(DEFAULT_VALUES_PER_STATEMENT),
+                                db_dialect.as_ref(),
+                                &mut file,
+                                on_progress,
+                            )
+                            .await?
+                        }
+                        _ => {
+                            return Err(AppError::BadRequest {
+                                message: "Invalid SQL statement type".to_string(),
+                            });
+                        }
+                    };
+                    if use_transaction {
+                        write!(file, "\n{}\n", db_dialect.commit_transaction())?;
+                    }
+                    file.flush()?;
+                }
             }
-            _ => unreachable!(), // This case is handled above
+            Ok(())
+        }
+        .await;
+        if let Err(err) = written {
+            // Don't leave the temporary file of a failed append behind
+            if appending {
+                let _ = fs::remove_file(&file_path);
+            }
+            return Err(err);
+        }
+
+        if appending {
+            target.finish_append(&file_path)?;
         }
 
         Ok(WriterResult {
             query_time: time_difference_from_now(start),
-            file_name: fs::canonicalize(&downloads_dir)?.display().to_string(),
+            file_name: fs::canonicalize(target.path())?.display().to_string(),
+        })
+    })
+    .await
+}
+
+/// Loads the script's result into a MySQL table through a saved connection
+#[command]
+pub async fn export_to_database(
+    app: AppHandle,
+    sql: String,
+    connection: String,
+    table_name: String,
+    create_table: Option<bool>,
+    truncate: Option<bool>,
+    batch_size: Option<usize>,
+    query_id: Option<String>,
+) -> AppResult<ExportDatabaseResult> {
+    let task_app = app.clone();
+    let progress_id = query_id.clone();
+    run_cancellable_async(&task_app, query_id, move || async move {
+        let conn = run_blocking(move || load_connection_string(&connection)).await?;
+        if connection_kind(&conn)? != "mysql" {
+            return Err(AppError::BadRequest {
+                message: "Only MySQL connections can be exported to".to_string(),
+            });
+        }
+
+        let start = Utc::now();
+        let (mut context, statements) = app
+            .state::<QuerySession>()
+            .register(&sql, None, None)
+            .await?;
+        let df = get_script_data_frame(&mut context, &statements).await?;
+
+        let mut options = MySqlExportOptions::new(table_name)
+            .with_create_table(create_table.unwrap_or(false))
+            .with_truncate(truncate.unwrap_or(false));
+        if let Some(batch_size) = batch_size {
+            options = options.with_batch_size(batch_size);
+        }
+
+        // Connects the same way, and through the same session pool, as read_mysql
+        let pool = mysql_pool(
+            &context,
+            conn,
+            DEFAULT_MYSQL_SSLMODE.to_string(),
+            None,
+            DEFAULT_CONNECT_TIMEOUT,
+        )
+        .await?;
+        let rows_written = write_mysql(df, &pool, &options, |rows_written| {
+            let _ = app.emit(
+                EXPORT_PROGRESS_EVENT,
+                ExportProgress {
+                    query_id: progress_id.clone(),
+                    rows_written,
+                },
+            );
+        })
+        .await?;
+
+        Ok(ExportDatabaseResult {
+            query_time: time_difference_from_now(start),
+            rows_written,
         })
     })
     .await
 }
+
+/// Validates a Parquet codec such as `snappy`, `zstd(3)` or `gzip(6)` before the query runs.
+/// gzip, brotli and zstd need a level, so they get a default one when none is given.
+fn parquet_compression(compression: Option<String>) -> AppResult<Option<String>> {
+    let Some(compression) = compression else {
+        return Ok(None);
+    };
+
+    let compression = compression.trim().to_lowercase();
+    let invalid = |reason: String| AppError::BadRequest {
+        message: format!(
+            "Unsupported parquet compression: '{}'. {}",
+            compression, reason
+        ),
+    };
+
+    let (codec, level) = match compression.split_once('(') {
+        Some((codec, level)) => {
+            let level = level
+                .strip_suffix(')')
+                .and_then(|level| level.trim().parse::<u32>().ok())
+                .ok_or_else(|| invalid("The level must be a number, e.g. zstd(3)".to_string()))?;
+            (codec.trim(), Some(level))
+        }
+        None => (compression.as_str(), None),
+    };
+
+    // (minimum, maximum, default) level
+    let levels = match codec {
+        "uncompressed" | "snappy" | "lz4" | "lz4_raw" => None,
+        "gzip" => Some((0, 10, 6)),
+        "brotli" => Some((0, 11, 1)),
+        "zstd" => Some((1, 22, 3)),
+        _ => {
+            return Err(invalid(
+                "Supported: uncompressed, snappy, gzip, brotli, lz4, lz4_raw, zstd".to_string(),
+            ))
+        }
+    };
+
+    match (levels, level) {
+        (None, None) => Ok(Some(codec.to_string())),
+        (None, Some(_)) => Err(invalid(format!("{} takes no level", codec))),
+        (Some((min, max, default)), level) => {
+            let level = level.unwrap_or(default);
+            if (min..=max).contains(&level) {
+                Ok(Some(format!("{}({})", codec, level)))
+            } else {
+                Err(invalid(format!(
+                    "The {} level must be between {} and {}",
+                    codec, min, max
+                )))
+            }
+        }
+    }
+}
@@ -1,28 +1,47 @@
 use crate::context::error::AppError;
+use crate::context::pools::{pool_key, ConnectionPools};
 use crate::context::schema::AppResult;
-use crate::reader::excel::ExcelReader;
+use crate::context::sources::{SourcePins, SourceTables};
+use crate::reader::excel::{CellRange, ExcelReader, ExcelTable};
+use crate::reader::json::JsonReader;
+use crate::reader::sqlite::SqliteTable;
 use crate::sql::parse::{get_function_args, parse_statements};
-use async_recursion::async_recursion;
+use crate::utils::connection_utils::load_connection_string;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::dataframe::DataFrame;
-use datafusion::prelude::{CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, SessionContext};
+use datafusion::prelude::{
+    CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, SessionConfig, SessionContext,
+};
 use datafusion::sql::TableReference;
 use datafusion_table_providers::{
-    mysql::MySQLTableFactory, sql::db_connection_pool::mysqlpool::MySQLConnectionPool,
+    mysql::MySQLTableFactory,
+    postgres::PostgresTableFactory,
+    sql::db_connection_pool::{
+        mysqlpool::MySQLConnectionPool, postgrespool::PostgresConnectionPool,
+    },
     util::secrets::to_secret_map,
 };
-use sqlparser::ast::SetExpr::Select;
+use glob::glob;
 use sqlparser::ast::{
-    Expr, FunctionArg, FunctionArgExpr, Offset, OffsetRows, Query, Statement, TableFactor,
-    TableFunctionArgs, Value,
+    Expr, FunctionArg, FunctionArgExpr, ObjectName, Offset, OffsetRows, Statement, TableFactor,
+    TableFunctionArgs, Value, VisitMut, VisitorMut,
 };
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::future::Future;
-use std::pin::Pin;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::ops::{ControlFlow, Deref};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Prefix of the tables generated for `read_*` table functions
+pub const SOURCE_TABLE_PREFIX: &str = "__easydb_source";
 
 pub fn get_sql_context() -> SessionContext {
-    SessionContext::new()
+    let config = SessionConfig::new()
+        .with_extension(Arc::new(ConnectionPools::default()))
+        .with_extension(Arc::new(SourceTables::default()));
+    SessionContext::new_with_config(config)
 }
 
 pub async fn get_data_frame(ctx: &mut SessionContext, sql: &String) -> AppResult<DataFrame> {
@@ -143,7 +162,7 @@ pub fn get_path(args: &mut Option<TableFunctionArgs>) -> AppResult<String> {
 pub fn read_excel(
     mut reader: ExcelReader,
     args: &mut Option<TableFunctionArgs>,
-) -> AppResult<RecordBatch> {
+) -> AppResult<ExcelTable> {
     let args = get_function_args(args);
 
     if let Some(args) = args {
@@ -165,6 +184,40 @@ pub fn read_excel(
                             }
                         }
                     }
+                    "batch_size" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))) = arg {
+                            reader = reader.with_batch_size(parse_count_arg(value, "batch_size")?);
+                        }
+                    }
+                    "range" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(
+                            value,
+                        ))) = arg
+                        {
+                            reader = reader.with_range(CellRange::parse(value)?);
+                        }
+                    }
+                    "header_row" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))) = arg {
+                            reader = reader.with_header_row(parse_count_arg(value, "header_row")?);
+                        }
+                    }
+                    "has_header" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Boolean(value))) = arg {
+                            reader = reader.with_has_header(*value);
+                        }
+                    }
+                    "skip_rows" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))) = arg {
+                            reader = reader.with_skip_rows(parse_count_arg(value, "skip_rows")?);
+                        }
+                    }
+                    "skip_footer" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))) = arg {
+                            reader =
+                                reader.with_skip_footer(parse_count_arg(value, "skip_footer")?);
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -174,25 +227,37 @@ pub fn read_excel(
     reader.finish().map_err(|e| e.into())
 }
 
-pub async fn register_mysql(
-    ctx: &mut SessionContext,
-    table_name: &String,
-    table_path: &String,
+fn parse_count_arg(value: &str, name: &str) -> AppResult<usize> {
+    value.parse().map_err(|_| AppError::BadRequest {
+        message: format!("Invalid {}", name),
+    })
+}
+
+pub fn read_json(
+    mut reader: JsonReader,
     args: &mut Option<TableFunctionArgs>,
-) -> AppResult<()> {
+) -> AppResult<RecordBatch> {
     let args = get_function_args(args);
-    let mut conn: Option<String> = None;
 
     if let Some(args) = args {
         for arg in args {
             if let FunctionArg::Named { name, arg, .. } = arg {
                 match name.value.as_str() {
-                    "conn" => {
+                    "records_path" => {
                         if let FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(
                             value,
                         ))) = arg
                         {
-                            conn = Some(value.to_string());
+                            reader = reader.with_records_path(value.to_string());
+                        }
+                    }
+                    "infer_schema_length" => {
+                        if let FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))) = arg {
+                            reader = reader.with_infer_schema_length(value.parse().map_err(
+                                |_| AppError::BadRequest {
+                                    message: "Invalid infer_schema_length".to_string(),
+                                },
+                            )?);
                         }
                     }
                     _ => {}
@@ -201,139 +266,577 @@ pub async fn register_mysql(
         }
     }
 
-    if conn.is_none() {
-        return Err(AppError::BadRequest {
-            message: "'conn' parameter is required".to_string(),
-        });
+    reader.finish()
+}
+
+/// Seconds to wait for a database connection before giving up
+pub const DEFAULT_CONNECT_TIMEOUT: u64 = 30;
+
+/// SSL mode of `read_mysql` when none is given, also used to test saved connections
+pub const DEFAULT_MYSQL_SSLMODE: &str = "preferred";
+
+/// SSL mode of `read_postgres` when none is given, also used to test saved connections
+pub const DEFAULT_POSTGRES_SSLMODE: &str = "prefer";
+
+/// Registers a MySQL table. The provider turns projections, supported filters and
+/// LIMIT into the SQL it sends to MySQL, so only the matching rows are transferred.
+pub async fn register_mysql(
+    ctx: &mut SessionContext,
+    table_name: &String,
+    table_path: &String,
+    args: &mut Option<TableFunctionArgs>,
+) -> AppResult<()> {
+    let args = get_function_args(args);
+    let mut conn: Option<String> = None;
+    let mut conn_name: Option<String> = None;
+    let mut sslmode = DEFAULT_MYSQL_SSLMODE.to_string();
+    let mut ssl_rootcert: Option<String> = None;
+    let mut connect_timeout = DEFAULT_CONNECT_TIMEOUT;
+
+    if let Some(args) = args {
+        for arg in args {
+            if let FunctionArg::Named { name, arg, .. } = arg {
+                match (name.value.as_str(), arg) {
+                    (
+                        "conn",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        conn = Some(value.to_string());
+                    }
+                    ("conn", FunctionArgExpr::Expr(Expr::Identifier(ident)))
+                        if ident.value.starts_with('@') =>
+                    {
+                        conn_name = Some(ident.value[1..].to_string());
+                    }
+                    (
+                        "conn_name",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        conn_name = Some(value.to_string());
+                    }
+                    (
+                        "sslmode",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        sslmode = mysql_sslmode(value)?;
+                    }
+                    (
+                        "ssl_rootcert",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        ssl_rootcert = Some(value.to_string());
+                    }
+                    (
+                        "connect_timeout",
+                        FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))),
+                    ) => {
+                        connect_timeout = value.parse().map_err(|_| AppError::BadRequest {
+                            message: format!("Invalid connect_timeout: {}", value),
+                        })?;
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
 
-    let mysql_params = to_secret_map(HashMap::from([
-        ("connection_string".to_string(), conn.unwrap()),
-        ("sslmode".to_string(), "disabled".to_string()),
-    ]));
+    let conn = resolve_connection(conn, conn_name).await?;
+    let pool = mysql_pool(ctx, conn, sslmode, ssl_rootcert, connect_timeout).await?;
 
-    // Create MySQL connection pool
-    let mysql_pool = Arc::new(MySQLConnectionPool::new(mysql_params).await?);
+    let timeout = Duration::from_secs(connect_timeout);
+    let timed_out = || AppError::BadRequest {
+        message: format!("Timed out connecting to MySQL after {}s", connect_timeout),
+    };
 
     // Create MySQL table provider factory
     // Used to generate TableProvider instances that can read MySQL table data
-    let table_factory = MySQLTableFactory::new(mysql_pool);
+    let table_factory = MySQLTableFactory::new(pool);
+    let provider = tokio::time::timeout(
+        timeout,
+        table_factory.table_provider(TableReference::bare(table_path.clone())),
+    )
+    .await
+    .map_err(|_| timed_out())??;
 
-    ctx.register_table(
-        table_name,
-        table_factory
-            .table_provider(TableReference::bare(table_path.clone()))
-            .await?,
-    )?;
+    ctx.register_table(table_name, provider)?;
 
     Ok(())
 }
 
-pub async fn register_table(
-    ctx: &mut SessionContext,
-    relation: &mut TableFactor,
-    table_count: i32,
-) -> AppResult<i32> {
-    if let TableFactor::Table { name, args, .. } = relation {
-        if args.is_none() {
-            return Ok(table_count);
+/// Returns the session's MySQL pool for these settings, opening it within
+/// `connect_timeout` seconds when there is none yet. Used by `read_mysql` and by
+/// exports to MySQL, so both connect the same way.
+pub async fn mysql_pool(
+    ctx: &SessionContext,
+    conn: String,
+    sslmode: String,
+    ssl_rootcert: Option<String>,
+    connect_timeout: u64,
+) -> AppResult<Arc<MySQLConnectionPool>> {
+    let mut params = HashMap::from([
+        ("connection_string".to_string(), conn),
+        ("sslmode".to_string(), sslmode),
+    ]);
+    if let Some(ssl_rootcert) = ssl_rootcert {
+        params.insert("sslrootcert".to_string(), ssl_rootcert);
+    }
+
+    let key = pool_key(&params, connect_timeout);
+    let pools = ctx.copied_config().get_extension::<ConnectionPools>();
+    if let Some(pool) = pools.as_ref().and_then(|pools| pools.mysql(&key)) {
+        return Ok(pool);
+    }
+
+    let pool = tokio::time::timeout(
+        Duration::from_secs(connect_timeout),
+        MySQLConnectionPool::new(to_secret_map(params)),
+    )
+    .await
+    .map_err(|_| AppError::BadRequest {
+        message: format!("Timed out connecting to MySQL after {}s", connect_timeout),
+    })??;
+    let pool = Arc::new(pool);
+    Ok(match &pools {
+        Some(pools) => pools.insert_mysql(key, pool),
+        None => pool,
+    })
+}
+
+/// Takes the inline `conn` string, or looks up the one saved under `conn => @name`
+/// or `conn_name => 'name'`, so credentials don't have to appear in the SQL
+async fn resolve_connection(conn: Option<String>, conn_name: Option<String>) -> AppResult<String> {
+    match (conn, conn_name) {
+        (Some(conn), _) => Ok(conn),
+        (None, Some(name)) => {
+            tokio::task::spawn_blocking(move || load_connection_string(&name)).await?
         }
-        let table_name = format!("__easydb_source{}", table_count);
-        let table_path = get_table_path(args)?;
+        (None, None) => Err(AppError::BadRequest {
+            message: "'conn' or 'conn_name' parameter is required".to_string(),
+        }),
+    }
+}
 
-        match name.to_string().as_str() {
-            "read_csv" => {
-                ctx.register_csv(&table_name, &table_path, get_csv_read_options(args)?)
-                    .await?
-            }
-            "read_tsv" => {
-                let mut options = get_csv_read_options(args)?;
-                options.delimiter = b'\t';
-                options.file_extension = ".tsv";
-                ctx.register_csv(&table_name, &table_path, options).await?
-            }
-            "read_ndjson" => {
-                ctx.register_json(&table_name, &table_path, NdJsonReadOptions::default())
-                    .await?
-            }
-            "read_parquet" => {
-                ctx.register_parquet(&table_name, &table_path, ParquetReadOptions::default())
-                    .await?
-            }
-            "read_excel" | "read_xlsx" => {
-                ctx.register_batch(&table_name, read_excel(ExcelReader::new(table_path), args)?)?;
-            }
-            "read_mysql" => {
-                register_mysql(ctx, &table_name, &table_path, args).await?;
-            }
-            _ => {
-                return Err(AppError::BadRequest {
-                    message: format!("'{}' is not a supported table function", table_name),
-                })
+/// Accepts the PostgreSQL spellings of the SSL modes as well as MySQL's own
+fn mysql_sslmode(value: &str) -> AppResult<String> {
+    match value.to_lowercase().as_str() {
+        "disable" | "disabled" => Ok("disabled".to_string()),
+        "prefer" | "preferred" => Ok("preferred".to_string()),
+        "require" | "required" => Ok("required".to_string()),
+        _ => Err(AppError::BadRequest {
+            message: format!(
+                "Unsupported sslmode '{}', expected disabled, preferred or required",
+                value
+            ),
+        }),
+    }
+}
+
+pub async fn register_postgres(
+    ctx: &mut SessionContext,
+    table_name: &String,
+    table_path: &String,
+    args: &mut Option<TableFunctionArgs>,
+) -> AppResult<()> {
+    let args = get_function_args(args);
+    let mut conn: Option<String> = None;
+    let mut conn_name: Option<String> = None;
+    let mut sslmode = DEFAULT_POSTGRES_SSLMODE.to_string();
+    let mut ssl_rootcert: Option<String> = None;
+    let mut pool_size: Option<String> = None;
+    let mut connect_timeout = DEFAULT_CONNECT_TIMEOUT;
+
+    if let Some(args) = args {
+        for arg in args {
+            if let FunctionArg::Named { name, arg, .. } = arg {
+                match (name.value.as_str(), arg) {
+                    (
+                        "conn",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        conn = Some(value.to_string());
+                    }
+                    ("conn", FunctionArgExpr::Expr(Expr::Identifier(ident)))
+                        if ident.value.starts_with('@') =>
+                    {
+                        conn_name = Some(ident.value[1..].to_string());
+                    }
+                    (
+                        "conn_name",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        conn_name = Some(value.to_string());
+                    }
+                    (
+                        "sslmode",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        sslmode = value.to_string();
+                    }
+                    (
+                        "ssl_rootcert",
+                        FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                    ) => {
+                        ssl_rootcert = Some(value.to_string());
+                    }
+                    ("pool_size", FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _)))) => {
+                        pool_size = Some(value.to_string());
+                    }
+                    (
+                        "connect_timeout",
+                        FunctionArgExpr::Expr(Expr::Value(Value::Number(value, _))),
+                    ) => {
+                        connect_timeout = value.parse().map_err(|_| AppError::BadRequest {
+                            message: format!("Invalid connect_timeout: {}", value),
+                        })?;
+                    }
+                    _ => {}
+                }
             }
         }
+    }
+
+    let conn = resolve_connection(conn, conn_name).await?;
 
-        *name = sqlparser::ast::ObjectName(vec![table_name.as_str().into()]);
-        *args = None;
+    let mut params = HashMap::from([
+        ("connection_string".to_string(), conn),
+        ("sslmode".to_string(), sslmode),
+    ]);
+    if let Some(ssl_rootcert) = ssl_rootcert {
+        params.insert("sslrootcert".to_string(), ssl_rootcert);
     }
+    if let Some(pool_size) = pool_size {
+        params.insert("connection_pool_size".to_string(), pool_size);
+    }
+
+    let timeout = Duration::from_secs(connect_timeout);
+    let timed_out = || AppError::BadRequest {
+        message: format!(
+            "Timed out connecting to PostgreSQL after {}s",
+            connect_timeout
+        ),
+    };
+
+    let key = pool_key(&params, connect_timeout);
+    let pools = ctx.copied_config().get_extension::<ConnectionPools>();
+
+    let postgres_pool = match pools.as_ref().and_then(|pools| pools.postgres(&key)) {
+        Some(pool) => pool,
+        None => {
+            let pool =
+                tokio::time::timeout(timeout, PostgresConnectionPool::new(to_secret_map(params)))
+                    .await
+                    .map_err(|_| timed_out())??;
+            let pool = Arc::new(pool);
+            match &pools {
+                Some(pools) => pools.insert_postgres(key, pool),
+                None => pool,
+            }
+        }
+    };
+
+    // 'schema.table' is split into its parts, unquoted names are folded to lower case
+    let table_factory = PostgresTableFactory::new(postgres_pool);
+    let provider = tokio::time::timeout(
+        timeout,
+        table_factory.table_provider(TableReference::from(table_path.as_str())),
+    )
+    .await
+    .map_err(|_| timed_out())??;
 
-    Ok(table_count + 1)
+    ctx.register_table(table_name, provider)?;
+
+    Ok(())
 }
 
-#[async_recursion]
-pub async fn convert_table_name(
+pub fn register_sqlite(
     ctx: &mut SessionContext,
-    query: &mut Box<Query>,
-    mut table_count: i32,
-) -> AppResult<i32> {
-    if let Some(with) = &mut query.with {
-        for cte in &mut with.cte_tables {
-            table_count = convert_table_name(ctx, &mut cte.query, table_count).await?;
+    table_name: &String,
+    table_path: &String,
+    args: &mut Option<TableFunctionArgs>,
+) -> AppResult<()> {
+    let args = get_function_args(args);
+    let mut table: Option<String> = None;
+
+    if let Some(args) = args {
+        for arg in args {
+            if let FunctionArg::Named { name, arg, .. } = arg {
+                if let (
+                    "table",
+                    FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(value))),
+                ) = (name.value.as_str(), arg)
+                {
+                    table = Some(value.to_string());
+                }
+            }
         }
     }
 
-    if let Select(select) = &mut *query.body {
-        for table_with_joins in &mut select.from {
-            match &mut table_with_joins.relation {
-                TableFactor::Derived { subquery, .. } => {
-                    table_count = convert_table_name(ctx, subquery, table_count).await?;
-                }
-                relation => {
-                    table_count = register_table(ctx, relation, table_count).await?;
+    let table = table.ok_or_else(|| AppError::BadRequest {
+        message: "'table' parameter is required".to_string(),
+    })?;
+
+    ctx.register_table(
+        table_name,
+        Arc::new(SqliteTable::try_new(table_path.clone(), table)?),
+    )?;
+
+    Ok(())
+}
+
+/// A `read_*` table function lifted out of the statement, waiting to be registered
+pub struct TableSource {
+    /// Identifies the call, independent of the version of the files it reads
+    pub key: u64,
+    pub table_name: String,
+    pub function_name: String,
+    pub args: Option<TableFunctionArgs>,
+}
+
+/// Replaces every table function call in the AST with a generated table name.
+/// The name is derived from the call itself and the modification times of the files it
+/// reads, so the same source is registered only once per session and re-read after
+/// any of the files changes.
+struct TableFunctionCollector {
+    sources: Vec<TableSource>,
+}
+
+impl VisitorMut for TableFunctionCollector {
+    type Break = ();
+
+    fn pre_visit_table_factor(&mut self, table_factor: &mut TableFactor) -> ControlFlow<()> {
+        if let TableFactor::Table { name, args, .. } = table_factor {
+            if let Some(table_args) = args {
+                let mut hasher = DefaultHasher::new();
+                name.to_string().hash(&mut hasher);
+                for arg in &table_args.args {
+                    arg.to_string().hash(&mut hasher);
                 }
-            }
-            for join in &mut table_with_joins.joins {
-                match &mut join.relation {
-                    TableFactor::Derived { subquery, .. } => {
-                        table_count =
-                            convert_table_name(ctx, subquery, table_count).await?;
-                    }
-                    relation => {
-                        table_count =
-                            register_table(ctx, &mut join.relation, table_count).await?;
-                    }
+                let key = hasher.finish();
+                if let Ok(path) = get_table_path(args) {
+                    hash_file_versions(&path, &mut hasher);
                 }
+
+                let table_name = format!("{}_{:x}", SOURCE_TABLE_PREFIX, hasher.finish());
+                self.sources.push(TableSource {
+                    key,
+                    table_name: table_name.clone(),
+                    function_name: name.to_string(),
+                    args: args.take(),
+                });
+                *name = ObjectName(vec![table_name.as_str().into()]);
             }
         }
+        ControlFlow::Continue(())
     }
+}
 
-    Ok(table_count)
+/// Hashes the modification time of the file, or of every file a glob matches, so new
+/// and changed matches are picked up as well
+fn hash_file_versions(path: &str, hasher: &mut DefaultHasher) {
+    let Ok(paths) = glob(path) else {
+        return;
+    };
+    for path in paths.flatten() {
+        if let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) {
+            path.hash(hasher);
+            modified.hash(hasher);
+        }
+    }
 }
 
+pub async fn register_table(ctx: &mut SessionContext, source: &mut TableSource) -> AppResult<()> {
+    let table_name = &source.table_name;
+    let args = &mut source.args;
+    let table_path = get_table_path(args)?;
+
+    match source.function_name.as_str() {
+        "read_csv" => {
+            ctx.register_csv(table_name, &table_path, get_csv_read_options(args)?)
+                .await?
+        }
+        "read_tsv" => {
+            let mut options = get_csv_read_options(args)?;
+            options.delimiter = b'\t';
+            options.file_extension = ".tsv";
+            ctx.register_csv(table_name, &table_path, options).await?
+        }
+        "read_ndjson" => {
+            ctx.register_json(table_name, &table_path, NdJsonReadOptions::default())
+                .await?
+        }
+        "read_json" => {
+            ctx.register_batch(table_name, read_json(JsonReader::new(table_path), args)?)?;
+        }
+        "read_parquet" => {
+            ctx.register_parquet(table_name, &table_path, ParquetReadOptions::default())
+                .await?
+        }
+        "read_excel" | "read_xlsx" | "read_ods" => {
+            let table = read_excel(ExcelReader::new(table_path), args)?;
+            ctx.register_table(table_name, Arc::new(table))?;
+        }
+        "read_mysql" => {
+            register_mysql(ctx, table_name, &table_path, args).await?;
+        }
+        "read_postgres" => {
+            register_postgres(ctx, table_name, &table_path, args).await?;
+        }
+        "read_sqlite" => {
+            register_sqlite(ctx, table_name, &table_path, args)?;
+        }
+        function_name => {
+            return Err(AppError::BadRequest {
+                message: format!("'{}' is not a supported table function", function_name),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// Registers every table function found anywhere in the statement (CTEs, set operations,
+/// subqueries in expressions, nested joins, LATERAL subqueries) and rewrites it in place.
+/// Sources already registered in this session are reused instead of being read again.
+/// Each one is pinned in `pins` for the script, and outdated or least recently used
+/// ones no other script uses are deregistered.
+pub async fn convert_table_functions(
+    ctx: &mut SessionContext,
+    statement: &mut Statement,
+    pins: &mut SourcePins,
+) -> AppResult<()> {
+    let mut collector = TableFunctionCollector {
+        sources: Vec::new(),
+    };
+    let _ = statement.visit(&mut collector);
+
+    for source in &mut collector.sources {
+        // Pinned first, so the table can't be evicted between the check and its use
+        pins.pin(source.key, &source.table_name)?;
+        if !ctx.table_exist(source.table_name.as_str())? {
+            register_table(ctx, source).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewritten statements of a registered script. Its sources stay registered as long as
+/// it is alive, so it must be kept until the script has been planned.
+pub struct Script {
+    statements: Vec<String>,
+    _pins: SourcePins,
+}
+
+impl Deref for Script {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.statements
+    }
+}
+
+/// Registers every statement of a script and returns the rewritten SQL of each one.
+/// `limit` and `offset` only apply to the last statement, whose result is displayed.
 pub async fn register(
     ctx: &mut SessionContext,
     sql: &str,
     limit: Option<usize>,
     offset: Option<usize>,
-) -> AppResult<String> {
+) -> AppResult<Script> {
     let mut ast = parse_statements(sql)?;
 
-    let statement = ast.get_mut(0).ok_or(AppError::BadRequest {
+    if ast.is_empty() {
+        return Err(AppError::BadRequest {
+            message: "invalid SQL statement".to_string(),
+        });
+    }
+
+    let count = ast.len();
+    let mut statements = Vec::with_capacity(count);
+    let mut pins = SourcePins::new(ctx);
+    for (index, statement) in ast.iter_mut().enumerate() {
+        let (limit, offset) = if index == count - 1 {
+            (limit, offset)
+        } else {
+            (None, None)
+        };
+        statements.push(
+            register_statement(ctx, statement, limit, offset, &mut pins)
+                .await
+                .map_err(|err| err.at_statement(index, count))?,
+        );
+    }
+
+    Ok(Script {
+        statements,
+        _pins: pins,
+    })
+}
+
+/// Executes every statement but the last one and returns the DataFrame of the last
+pub async fn get_script_data_frame(
+    ctx: &mut SessionContext,
+    statements: &[String],
+) -> AppResult<DataFrame> {
+    let count = statements.len();
+    let (last, preceding) = statements.split_last().ok_or(AppError::BadRequest {
         message: "invalid SQL statement".to_string(),
     })?;
 
+    for (index, sql) in preceding.iter().enumerate() {
+        collect(ctx, sql)
+            .await
+            .map_err(|err| err.at_statement(index, count))?;
+    }
+
+    get_data_frame(ctx, last)
+        .await
+        .map_err(|err| err.at_statement(count - 1, count))
+}
+
+pub async fn collect_script(
+    ctx: &mut SessionContext,
+    statements: &[String],
+) -> AppResult<Vec<RecordBatch>> {
+    let count = statements.len();
+    get_script_data_frame(ctx, statements)
+        .await?
+        .collect()
+        .await
+        .map_err(|err| AppError::from(err).at_statement(count - 1, count))
+}
+
+async fn register_statement(
+    ctx: &mut SessionContext,
+    statement: &mut Statement,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    pins: &mut SourcePins,
+) -> AppResult<String> {
+    if !matches!(
+        statement,
+        Statement::Query(_)
+            | Statement::CreateView { .. }
+            | Statement::CreateTable(_)
+            | Statement::Drop { .. }
+    ) {
+        return Err(AppError::BadRequest {
+            message: "Only supports SELECT, CREATE VIEW, CREATE TABLE and DROP statements."
+                .to_string(),
+        });
+    }
+
+    convert_table_functions(ctx, statement, pins).await?;
+
+    // The session outlives the script, and every run replays its statements, so
+    // running it again must replace what the previous run created
+    match statement {
+        Statement::CreateView { or_replace, .. } => *or_replace = true,
+        Statement::CreateTable(create) if !create.if_not_exists => create.or_replace = true,
+        _ => {}
+    }
+
     if let Statement::Query(query) = statement {
-        convert_table_name(ctx, query, 0).await?;
         if limit.is_some() && query.limit.is_none() {
             query.limit = Some(Expr::Value(Value::Number(limit.unwrap().to_string(), true)));
         }
@@ -355,8 +858,6 @@ pub async fn register(
 
         Ok(query.to_string())
     } else {
-        Err(AppError::BadRequest {
-            message: "Only supports Select statements.".to_string(),
-        })
+        Ok(statement.to_string())
     }
 }
@@ -0,0 +1,164 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::stats::Precision;
+use datafusion::dataframe::DataFrame;
+use datafusion::physical_plan::{execute_stream, SendableRecordBatchStream};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Upper bound on the cursors kept open at once
+pub const MAX_OPEN_CURSORS: usize = 16;
+
+/// Cursors not fetched from for this long are closed, releasing their streams
+pub const CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// An open query whose results are pulled page by page from a DataFusion stream,
+/// instead of re-running the query with a new LIMIT/OFFSET for every page.
+pub struct QueryCursor {
+    stream: SendableRecordBatchStream,
+    pending: Option<RecordBatch>,
+    total_rows: Option<usize>,
+    exhausted: bool,
+}
+
+impl QueryCursor {
+    pub async fn open(df: DataFrame) -> AppResult<Self> {
+        let task_ctx = Arc::new(df.task_ctx());
+        let plan = df.create_physical_plan().await?;
+
+        // Only known up front when the plan can tell without running (e.g. in-memory tables)
+        let total_rows = match plan.partition_statistics(None)?.num_rows {
+            Precision::Exact(num_rows) => Some(num_rows),
+            _ => None,
+        };
+
+        Ok(Self {
+            stream: execute_stream(plan, task_ctx)?,
+            pending: None,
+            total_rows,
+            exhausted: false,
+        })
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+
+    pub fn total_rows(&self) -> Option<usize> {
+        self.total_rows
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted && self.pending.is_none()
+    }
+
+    /// Pulls up to `size` rows, keeping the rest of a split batch for the next page.
+    /// Reads one batch ahead, so the last page with rows already reports the cursor
+    /// as exhausted instead of leaving that to an extra, empty page.
+    pub async fn next_page(&mut self, size: usize) -> AppResult<Vec<RecordBatch>> {
+        let mut records = Vec::new();
+        let mut remaining = size;
+
+        while remaining > 0 {
+            let batch = match self.pending.take() {
+                Some(batch) => batch,
+                None => match self.next_batch().await? {
+                    Some(batch) => batch,
+                    None => break,
+                },
+            };
+
+            if batch.num_rows() > remaining {
+                self.pending = Some(batch.slice(remaining, batch.num_rows() - remaining));
+                records.push(batch.slice(0, remaining));
+                remaining = 0;
+            } else {
+                remaining -= batch.num_rows();
+                records.push(batch);
+            }
+        }
+
+        if self.pending.is_none() && !self.exhausted {
+            self.pending = self.next_batch().await?;
+        }
+
+        Ok(records)
+    }
+
+    /// Next non-empty batch of the stream, or `None` once it is done
+    async fn next_batch(&mut self) -> AppResult<Option<RecordBatch>> {
+        while let Some(batch) = self.stream.next().await {
+            let batch = batch?;
+            if batch.num_rows() > 0 {
+                return Ok(Some(batch));
+            }
+        }
+        self.exhausted = true;
+        Ok(None)
+    }
+}
+
+/// Open cursors held in Tauri managed state, keyed by the handle given to the frontend.
+/// Cursors the frontend never closes are dropped once idle for `CURSOR_IDLE_TIMEOUT`,
+/// or least recently used first when more than `MAX_OPEN_CURSORS` are open.
+#[derive(Default)]
+pub struct QueryCursors {
+    next_handle: AtomicU64,
+    cursors: Mutex<HashMap<u64, OpenCursor>>,
+}
+
+struct OpenCursor {
+    cursor: Arc<Mutex<QueryCursor>>,
+    last_used: Instant,
+}
+
+impl QueryCursors {
+    pub async fn insert(&self, cursor: QueryCursor) -> u64 {
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut cursors = self.cursors.lock().await;
+
+        let now = Instant::now();
+        cursors.retain(|_, open| now.duration_since(open.last_used) < CURSOR_IDLE_TIMEOUT);
+        while cursors.len() >= MAX_OPEN_CURSORS {
+            let oldest = cursors
+                .iter()
+                .min_by_key(|(_, open)| open.last_used)
+                .map(|(handle, _)| *handle);
+            match oldest {
+                Some(oldest) => cursors.remove(&oldest),
+                None => break,
+            };
+        }
+
+        cursors.insert(
+            handle,
+            OpenCursor {
+                cursor: Arc::new(Mutex::new(cursor)),
+                last_used: now,
+            },
+        );
+        handle
+    }
+
+    pub async fn get(&self, handle: u64) -> AppResult<Arc<Mutex<QueryCursor>>> {
+        let mut cursors = self.cursors.lock().await;
+        let open = cursors
+            .get_mut(&handle)
+            .filter(|open| open.last_used.elapsed() < CURSOR_IDLE_TIMEOUT)
+            .ok_or_else(|| AppError::BadRequest {
+                message: format!("Query handle {} not found or already closed", handle),
+            })?;
+        open.last_used = Instant::now();
+        Ok(open.cursor.clone())
+    }
+
+    pub async fn remove(&self, handle: u64) {
+        self.cursors.lock().await.remove(&handle);
+    }
+}
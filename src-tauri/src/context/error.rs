@@ -3,7 +3,7 @@ use calamine::XlsxError;
 use datafusion::arrow::error::ArrowError;
 use datafusion::error::DataFusionError;
 use datafusion_table_providers::mysql;
-use datafusion_table_providers::sql::db_connection_pool::mysqlpool;
+use datafusion_table_providers::sql::db_connection_pool::{mysqlpool, postgrespool};
 use derive_more::with_trait::{Display, Error};
 use glob::{GlobError, PatternError};
 use sqlparser::parser::ParserError;
@@ -15,6 +15,7 @@ pub enum AppError {
     BadRequest { message: String },
     FileNotFound { file_name: String },
     InternalServer { message: String },
+    Cancelled { query_id: String },
 }
 
 impl AppError {
@@ -23,6 +24,20 @@ impl AppError {
             AppError::BadRequest { message } => message.to_string(),
             AppError::FileNotFound { file_name } => format!("File not found: {}", file_name),
             AppError::InternalServer { message } => message.to_string(),
+            AppError::Cancelled { query_id } => format!("Query cancelled: {}", query_id),
+        }
+    }
+
+    /// Prefixes the message with the 1-based position of the failing statement,
+    /// unless the script only has one statement
+    pub fn at_statement(self, index: usize, count: usize) -> Self {
+        if count <= 1 || matches!(self, AppError::Cancelled { .. }) {
+            return self;
+        }
+        let message = format!("Statement {} failed: {}", index + 1, self.message());
+        match self {
+            AppError::InternalServer { .. } => AppError::InternalServer { message },
+            _ => AppError::BadRequest { message },
         }
     }
 
@@ -54,6 +69,15 @@ impl From<std::io::Error> for AppError {
     }
 }
 
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
 impl From<PatternError> for AppError {
     fn from(error: PatternError) -> Self {
         AppError::log_backtrace();
@@ -81,6 +105,24 @@ impl From<XlsxError> for AppError {
     }
 }
 
+impl From<calamine::Error> for AppError {
+    fn from(error: calamine::Error) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for AppError {
+    fn from(error: rust_xlsxwriter::XlsxError) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
 impl From<JoinError> for AppError {
     fn from(error: JoinError) -> Self {
         AppError::log_backtrace();
@@ -135,6 +177,15 @@ impl From<mysqlpool::Error> for AppError {
     }
 }
 
+impl From<postgrespool::Error> for AppError {
+    fn from(error: postgrespool::Error) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
 impl From<mysql::Error> for AppError {
     fn from(error: mysql::Error) -> Self {
         AppError::log_backtrace();
@@ -144,6 +195,33 @@ impl From<mysql::Error> for AppError {
     }
 }
 
+impl From<mysql_async::Error> for AppError {
+    fn from(error: mysql_async::Error) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<mysql_async::UrlError> for AppError {
+    fn from(error: mysql_async::UrlError) -> Self {
+        AppError::log_backtrace();
+        BadRequest {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<keyring::Error> for AppError {
+    fn from(error: keyring::Error) -> Self {
+        AppError::log_backtrace();
+        InternalServer {
+            message: error.to_string(),
+        }
+    }
+}
+
 impl From<Box<dyn std::error::Error + Send + Sync>> for AppError {
     fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
         AppError::log_backtrace();
@@ -151,4 +229,4 @@ impl From<Box<dyn std::error::Error + Send + Sync>> for AppError {
             message: error.to_string(),
         }
     }
-}
\ No newline at end of file
+}
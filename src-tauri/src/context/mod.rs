@@ -1,3 +1,8 @@
 pub mod context;
+pub mod cursor;
 pub mod error;
-pub mod schema;
\ No newline at end of file
+pub mod pools;
+pub mod running;
+pub mod schema;
+pub mod session;
+pub mod sources;
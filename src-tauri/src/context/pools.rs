@@ -0,0 +1,62 @@
+use datafusion_table_providers::sql::db_connection_pool::mysqlpool::MySQLConnectionPool;
+use datafusion_table_providers::sql::db_connection_pool::postgrespool::PostgresConnectionPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Database connection pools kept as a `SessionConfig` extension, so every query of a
+/// session reuses the pool opened for the same connection settings instead of
+/// reconnecting on each `read_mysql` or `read_postgres` call.
+#[derive(Default)]
+pub struct ConnectionPools {
+    mysql: Mutex<HashMap<String, Arc<MySQLConnectionPool>>>,
+    postgres: Mutex<HashMap<String, Arc<PostgresConnectionPool>>>,
+}
+
+impl ConnectionPools {
+    pub fn mysql(&self, key: &str) -> Option<Arc<MySQLConnectionPool>> {
+        self.mysql.lock().ok()?.get(key).cloned()
+    }
+
+    /// Caches the pool, keeping the one already stored if another query raced us
+    pub fn insert_mysql(
+        &self,
+        key: String,
+        pool: Arc<MySQLConnectionPool>,
+    ) -> Arc<MySQLConnectionPool> {
+        match self.mysql.lock() {
+            Ok(mut pools) => pools.entry(key).or_insert(pool).clone(),
+            Err(_) => pool,
+        }
+    }
+
+    pub fn postgres(&self, key: &str) -> Option<Arc<PostgresConnectionPool>> {
+        self.postgres.lock().ok()?.get(key).cloned()
+    }
+
+    /// Caches the pool, keeping the one already stored if another query raced us
+    pub fn insert_postgres(
+        &self,
+        key: String,
+        pool: Arc<PostgresConnectionPool>,
+    ) -> Arc<PostgresConnectionPool> {
+        match self.postgres.lock() {
+            Ok(mut pools) => pools.entry(key).or_insert(pool).clone(),
+            Err(_) => pool,
+        }
+    }
+}
+
+/// Identifies a pool by everything it was opened with, so changing any parameter or
+/// the connect timeout opens a new pool instead of reusing one set up differently.
+/// Parameters are sorted to keep the key independent of the map's order.
+pub fn pool_key(params: &HashMap<String, String>, connect_timeout: u64) -> String {
+    let mut params: Vec<_> = params.iter().collect();
+    params.sort();
+    let mut key = params
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("|");
+    key.push_str(&format!("|connect_timeout={}", connect_timeout));
+    key
+}
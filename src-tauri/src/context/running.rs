@@ -0,0 +1,59 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use futures::future::AbortHandle;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Queries currently executing, keyed by their id, so they can be aborted with
+/// `cancel_query`. Aborting the task drops the DataFusion streams, which stops their
+/// execution as well.
+#[derive(Default)]
+pub struct RunningQueries {
+    queries: Mutex<HashMap<String, AbortHandle>>,
+    next_id: AtomicU64,
+}
+
+impl RunningQueries {
+    /// Registers a query under the id the frontend passed in, or a generated one when
+    /// it passed none, and returns the id. An id that is still running is rejected,
+    /// as it would take over the other query's handle.
+    pub fn insert(&self, query_id: Option<String>, handle: AbortHandle) -> AppResult<String> {
+        let query_id = query_id.unwrap_or_else(|| {
+            format!("query-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        });
+
+        let mut queries = self.queries.lock().map_err(|e| AppError::InternalServer {
+            message: e.to_string(),
+        })?;
+        if queries.contains_key(&query_id) {
+            return Err(AppError::BadRequest {
+                message: format!("A query with id '{}' is already running", query_id),
+            });
+        }
+        queries.insert(query_id.clone(), handle);
+
+        Ok(query_id)
+    }
+
+    pub fn remove(&self, query_id: &str) {
+        if let Ok(mut queries) = self.queries.lock() {
+            queries.remove(query_id);
+        }
+    }
+
+    /// Returns `false` when the query already finished or never existed
+    pub fn cancel(&self, query_id: &str) -> bool {
+        let handle = match self.queries.lock() {
+            Ok(mut queries) => queries.remove(query_id),
+            Err(_) => None,
+        };
+        match handle {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
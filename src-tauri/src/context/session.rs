@@ -0,0 +1,42 @@
+use crate::context::context::{get_sql_context, register, Script};
+use crate::context::schema::AppResult;
+use datafusion::prelude::SessionContext;
+use tokio::sync::Mutex;
+
+/// Long-lived DataFusion session held in Tauri managed state, so registered sources,
+/// views and tables survive between `fetch` and `writer` calls.
+pub struct QuerySession {
+    ctx: Mutex<SessionContext>,
+}
+
+impl QuerySession {
+    pub fn new() -> Self {
+        Self {
+            ctx: Mutex::new(get_sql_context()),
+        }
+    }
+
+    /// Registers the script's sources under the session lock and returns a handle
+    /// to the shared context, so execution itself doesn't block other queries.
+    /// The sources stay registered until the returned script is dropped.
+    pub async fn register(
+        &self,
+        sql: &str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> AppResult<(SessionContext, Script)> {
+        let mut ctx = self.ctx.lock().await;
+        let statements = register(&mut ctx, sql, limit, offset).await?;
+        Ok((ctx.clone(), statements))
+    }
+
+    pub async fn context(&self) -> SessionContext {
+        self.ctx.lock().await.clone()
+    }
+}
+
+impl Default for QuerySession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
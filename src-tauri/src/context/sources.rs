@@ -0,0 +1,130 @@
+use crate::context::schema::AppResult;
+use datafusion::prelude::SessionContext;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Upper bound on the unused `read_*` tables kept registered in a session
+pub const MAX_SOURCE_TABLES: usize = 32;
+
+/// Tables registered for `read_*` table functions, kept as a `SessionConfig`
+/// extension so the long-lived session doesn't accumulate one table per file
+/// version or argument set.
+#[derive(Default)]
+pub struct SourceTables {
+    /// Least recently used first
+    tables: Mutex<Vec<SourceTable>>,
+}
+
+struct SourceTable {
+    key: u64,
+    table_name: String,
+    /// Number of registered scripts still using the table
+    pins: usize,
+}
+
+impl SourceTables {
+    /// Records that `table_name` is the current version of the source `key` and pins it
+    /// until `unpin`. Tables no script uses any more are deregistered: older versions of
+    /// a source (e.g. before the file changed) and the least recently used ones beyond
+    /// `MAX_SOURCE_TABLES`.
+    pub fn pin(&self, ctx: &SessionContext, key: u64, table_name: &str) -> AppResult<()> {
+        let Ok(mut tables) = self.tables.lock() else {
+            return Ok(());
+        };
+
+        let mut table = match tables.iter().position(|t| t.table_name == table_name) {
+            Some(index) => tables.remove(index),
+            None => SourceTable {
+                key,
+                table_name: table_name.to_string(),
+                pins: 0,
+            },
+        };
+        table.pins += 1;
+        tables.push(table);
+
+        // Deregistered under the lock, so a script pinning the table meanwhile
+        // registers it again instead of finding it about to disappear
+        evict(ctx, &mut tables)
+    }
+
+    /// Releases one pin of each table, once its script has been planned or has failed
+    pub fn unpin(&self, ctx: &SessionContext, table_names: &[String]) -> AppResult<()> {
+        let Ok(mut tables) = self.tables.lock() else {
+            return Ok(());
+        };
+
+        for table_name in table_names {
+            if let Some(table) = tables.iter_mut().find(|t| &t.table_name == table_name) {
+                table.pins = table.pins.saturating_sub(1);
+            }
+        }
+
+        evict(ctx, &mut tables)
+    }
+}
+
+/// Deregisters the unpinned tables replaced by a newer version of their source, then
+/// the least recently used unpinned ones beyond `MAX_SOURCE_TABLES`
+fn evict(ctx: &SessionContext, tables: &mut Vec<SourceTable>) -> AppResult<()> {
+    let mut latest = HashSet::new();
+    let mut superseded = tables
+        .iter()
+        .rev()
+        .map(|table| !latest.insert(table.key))
+        .collect::<Vec<_>>();
+    superseded.reverse();
+
+    let mut excess = tables.len().saturating_sub(MAX_SOURCE_TABLES);
+    let mut stale = Vec::new();
+    let mut index = 0;
+    tables.retain(|table| {
+        let superseded = superseded[index];
+        index += 1;
+        if table.pins > 0 || !(superseded || excess > 0) {
+            return true;
+        }
+        excess = excess.saturating_sub(1);
+        stale.push(table.table_name.clone());
+        false
+    });
+
+    for table_name in stale {
+        ctx.deregister_table(table_name.as_str())?;
+    }
+    Ok(())
+}
+
+/// Sources used by a registered script, pinned until the script is dropped so that
+/// other scripts registering their own sources can't deregister them in between
+pub struct SourcePins {
+    ctx: SessionContext,
+    source_tables: Option<Arc<SourceTables>>,
+    table_names: Vec<String>,
+}
+
+impl SourcePins {
+    pub fn new(ctx: &SessionContext) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            source_tables: ctx.copied_config().get_extension::<SourceTables>(),
+            table_names: Vec::new(),
+        }
+    }
+
+    pub fn pin(&mut self, key: u64, table_name: &str) -> AppResult<()> {
+        if let Some(source_tables) = &self.source_tables {
+            source_tables.pin(&self.ctx, key, table_name)?;
+            self.table_names.push(table_name.to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SourcePins {
+    fn drop(&mut self) {
+        if let Some(source_tables) = &self.source_tables {
+            let _ = source_tables.unpin(&self.ctx, &self.table_names);
+        }
+    }
+}
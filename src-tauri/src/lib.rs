@@ -1,8 +1,17 @@
 use crate::commands::ai::{ai_generate_sql, ai_repair_sql};
-use crate::commands::app::restart_app;
+use crate::commands::app::{get_history_enabled, restart_app, set_history_enabled};
+use crate::commands::connection::{
+    delete_connection, list_connections, save_connection, test_connection,
+};
 use crate::commands::files::list_excel_sheets;
-use crate::commands::query::{fetch, sql_history, writer};
+use crate::commands::query::{
+    cancel_query, close_query, drop_table, export_to_database, fetch, fetch_page, generate_ddl,
+    list_tables, open_query, sql_history, writer,
+};
 use crate::commands::utils::open_url;
+use crate::context::cursor::QueryCursors;
+use crate::context::running::RunningQueries;
+use crate::context::session::QuerySession;
 use crate::utils::db_utils;
 use tauri::Listener;
 
@@ -13,10 +22,14 @@ pub mod context;
 pub mod reader;
 pub mod sql;
 pub mod utils;
+pub mod writer;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(QuerySession::new())
+        .manage(QueryCursors::default())
+        .manage(RunningQueries::default())
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -33,9 +46,23 @@ pub fn run() {
             fetch,
             open_url,
             restart_app,
+            get_history_enabled,
+            set_history_enabled,
             sql_history,
             writer,
+            list_tables,
+            drop_table,
+            open_query,
+            fetch_page,
+            close_query,
+            cancel_query,
+            generate_ddl,
+            export_to_database,
             list_excel_sheets,
+            save_connection,
+            list_connections,
+            delete_connection,
+            test_connection,
             ai_generate_sql,
             ai_repair_sql
         ])
@@ -1,25 +1,40 @@
 use crate::context::error::AppError;
 use crate::context::schema::AppResult;
 use crate::utils::file_utils::find_files;
-use calamine::{open_workbook, Data, HeaderRow, Range, Reader, Xlsx};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use async_trait::async_trait;
+use calamine::{open_workbook_auto, Data, DataType as _, Reader, Sheets};
 use datafusion::arrow::array::{
-    Array, Float64Array, Int64Array, StringArray, TimestampNanosecondArray,
+    ArrayRef, Float64Builder, Int64Builder, StringBuilder, TimestampNanosecondBuilder,
 };
-use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
-use datafusion::arrow::record_batch::RecordBatch;
-use std::collections::HashSet;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use datafusion::catalog::Session;
+use datafusion::datasource::TableProvider;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::{Expr, TableType};
+use datafusion::physical_expr::LexOrdering;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::streaming::{PartitionStream, StreamingTableExec};
+use datafusion::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
+use glob::Pattern;
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
-pub struct ExcelReadOptions {}
-
-pub struct ExcelParseOptions {}
+const DEFAULT_BATCH_SIZE: usize = 8192;
+const DEFAULT_SHEET: &str = "Sheet1";
+/// Virtual column holding the sheet name when `sheet_name` is a pattern
+const SHEET_COLUMN: &str = "__sheet";
+/// Virtual column holding the file path when the path is a glob
+const FILE_COLUMN: &str = "__file";
 
 pub struct ExcelReader {
     path: String,
     sheet_name: Option<String>,
+    layout: SheetLayout,
     infer_schema_length: usize,
-    try_parse_dates: bool,
+    batch_size: usize,
 }
 
 impl ExcelReader {
@@ -27,184 +42,700 @@ impl ExcelReader {
         Self {
             path,
             sheet_name: None,
+            layout: SheetLayout::default(),
             infer_schema_length: 100,
-            try_parse_dates: false,
+            batch_size: DEFAULT_BATCH_SIZE,
         }
     }
 
+    /// A sheet name, or a glob such as `*` or `2024-*` to union all matching sheets
     pub fn with_sheet_name(mut self, sheet_name: String) -> Self {
         self.sheet_name = Some(sheet_name);
         self
     }
 
+    /// Only reads the cells inside the range, whose first row is row 1 for `header_row`
+    pub fn with_range(mut self, range: CellRange) -> Self {
+        self.layout.range = Some(range);
+        self
+    }
+
+    /// 1-based position of the header row, the rows above it are skipped
+    pub fn with_header_row(mut self, header_row: usize) -> Self {
+        self.layout.header_row = header_row.max(1);
+        self
+    }
+
+    /// Without a header the first row is data and the columns are named t1..tn
+    pub fn with_has_header(mut self, has_header: bool) -> Self {
+        self.layout.has_header = has_header;
+        self
+    }
+
+    /// Rows skipped between the header and the first data row
+    pub fn with_skip_rows(mut self, skip_rows: usize) -> Self {
+        self.layout.skip_rows = skip_rows;
+        self
+    }
+
+    /// Rows dropped at the end of the data, e.g. totals
+    pub fn with_skip_footer(mut self, skip_footer: usize) -> Self {
+        self.layout.skip_footer = skip_footer;
+        self
+    }
+
     pub fn with_infer_schema_length(mut self, infer_schema_length: usize) -> Self {
         self.infer_schema_length = infer_schema_length;
         self
     }
 
-    pub fn finish(self) -> AppResult<RecordBatch> {
-        let mut schema: Option<Schema> = None;
-        let mut string_data: Vec<Vec<Option<String>>> = Vec::new();
-        let mut int64_data: Vec<Vec<Option<i64>>> = Vec::new();
-        let mut float64_data: Vec<Vec<Option<f64>>> = Vec::new();
-        let mut timestamp_data: Vec<Vec<Option<i64>>> = Vec::new();
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
 
-        let default_sheet = "Sheet1".to_string();
+    /// Resolves the matching files and sheets and infers the schema from their first
+    /// rows. The data itself is only read when the returned table is scanned.
+    pub fn finish(self) -> AppResult<ExcelTable> {
         let files = find_files(&self.path)?;
-        for file in files {
-            let mut xlsx: Xlsx<_> = open_workbook(file)?;
-            let sheet_names = xlsx.sheet_names();
-            let range =
-                xlsx.with_header_row(HeaderRow::Row(0))
-                    .worksheet_range(match self.sheet_name {
-                        Some(ref sheet_name) => sheet_name,
-                        None => sheet_names.get(0).unwrap_or(&default_sheet)
-                    })?;
-
-            if schema.is_none() {
-                let inferred = infer_field_schema(&range, self.infer_schema_length)?;
-                let num_columns = inferred.fields().len();
-                string_data = vec![Vec::new(); num_columns];
-                int64_data = vec![Vec::new(); num_columns];
-                float64_data = vec![Vec::new(); num_columns];
-                timestamp_data = vec![Vec::new(); num_columns];
-                schema = Some(inferred);
-            }
 
-            if let Some(schema) = &schema {
-                for row in range.rows().skip(1) {
-                    for (i, cell) in row.iter().enumerate() {
-                        if let Some(field) = schema.fields().get(i) {
-                            match field.data_type() {
-                                DataType::Int64 => {
-                                    let value = match cell {
-                                        Data::Int(v) => Some(*v as i64),
-                                        Data::Float(v) => Some(*v as i64),
-                                        _ => None,
-                                    };
-                                    int64_data[i].push(value);
-                                }
-                                DataType::Float64 => {
-                                    let value = match cell {
-                                        Data::Float(v) => Some(*v),
-                                        Data::Int(v) => Some(*v as f64),
-                                        _ => None,
-                                    };
-                                    float64_data[i].push(value);
-                                }
-                                DataType::Timestamp(TimeUnit::Nanosecond, _) => {
-                                    let value = match cell {
-                                        Data::DateTime(dt) => {
-                                            // ExcelDateTime with "dates" feature: convert via chrono
-                                            // ExcelDateTime in calamine 0.30.1 with dates feature is a newtype
-                                            // We can convert it by using the underlying chrono::NaiveDateTime
-                                            // Try accessing via Deref or using a conversion method
-                                            let dt_str = dt.to_string();
-                                            NaiveDateTime::parse_from_str(
-                                                &dt_str,
-                                                "%Y-%m-%d %H:%M:%S",
-                                            )
-                                            .ok()
-                                            .or_else(|| {
-                                                NaiveDateTime::parse_from_str(&dt_str, "%Y-%m-%d")
-                                                    .ok()
-                                            })
-                                            .map(|naive_dt| {
-                                                let dt_utc =
-                                                    DateTime::<Utc>::from_naive_utc_and_offset(
-                                                        naive_dt, Utc,
-                                                    );
-                                                dt_utc.timestamp_nanos_opt()
-                                            })
-                                            .flatten()
-                                        }
-                                        _ => None,
-                                    };
-                                    timestamp_data[i].push(value);
-                                }
-                                _ => {
-                                    let value = match cell {
-                                        Data::Empty => None,
-                                        Data::String(s) => Some(s.clone()),
-                                        _ => Some(cell.to_string()),
-                                    };
-                                    string_data[i].push(value);
-                                }
-                            }
+        let sheet_pattern = match &self.sheet_name {
+            Some(sheet_name) if is_pattern(sheet_name) => Some(Pattern::new(sheet_name)?),
+            _ => None,
+        };
+        let sources = match &sheet_pattern {
+            Some(pattern) => {
+                let mut sources = Vec::new();
+                for file in &files {
+                    let workbook = open_workbook_auto(file)?;
+                    for sheet_name in workbook.sheet_names() {
+                        if pattern.matches(&sheet_name) {
+                            sources.push(SheetSource {
+                                file: file.clone(),
+                                sheet_name: Some(sheet_name),
+                            });
                         }
                     }
                 }
+                if sources.is_empty() {
+                    return Err(AppError::BadRequest {
+                        message: format!("No sheet matches '{}'", pattern),
+                    });
+                }
+                sources
             }
+            None => files
+                .iter()
+                .map(|file| SheetSource {
+                    file: file.clone(),
+                    sheet_name: self.sheet_name.clone(),
+                })
+                .collect::<Vec<_>>(),
+        };
+
+        // A single sheet is sampled from the first file only, matching sheets are
+        // all sampled and their columns unified by name
+        let sampled = if sheet_pattern.is_some() {
+            &sources[..]
+        } else {
+            &sources[..1]
+        };
+        let schemas = sampled
+            .iter()
+            .map(|source| self.infer_schema(source))
+            .collect::<AppResult<Vec<_>>>()?;
+        let mut fields = unify_schemas(&schemas).fields().to_vec();
+        let data_columns = fields.len();
+
+        if sheet_pattern.is_some() {
+            fields.push(Arc::new(Field::new(SHEET_COLUMN, DataType::Utf8, false)));
+        }
+        if is_pattern(&self.path) {
+            fields.push(Arc::new(Field::new(FILE_COLUMN, DataType::Utf8, false)));
         }
 
-        match schema {
-            Some(schema) => {
-                let num_rows = string_data
-                    .first()
-                    .map(|v| v.len())
-                    .or_else(|| int64_data.first().map(|v| v.len()))
-                    .or_else(|| float64_data.first().map(|v| v.len()))
-                    .or_else(|| timestamp_data.first().map(|v| v.len()))
-                    .unwrap_or(0);
-
-                let mut arrays = Vec::new();
-                for (i, field) in schema.fields().iter().enumerate() {
-                    let array: Arc<dyn Array> = match field.data_type() {
-                        DataType::Int64 => Arc::new(Int64Array::from(int64_data[i].clone())),
-                        DataType::Float64 => Arc::new(Float64Array::from(float64_data[i].clone())),
-                        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
-                            Arc::new(TimestampNanosecondArray::from(timestamp_data[i].clone()))
-                        }
-                        _ => Arc::new(StringArray::from(string_data[i].clone())),
+        Ok(ExcelTable {
+            sources,
+            layout: self.layout,
+            schema: Arc::new(Schema::new(fields)),
+            data_columns,
+            batch_size: self.batch_size,
+        })
+    }
+
+    fn infer_schema(&self, source: &SheetSource) -> AppResult<Schema> {
+        with_sheet_rows(
+            &source.file,
+            source.sheet_name.as_deref(),
+            &self.layout,
+            |headers, rows| {
+                let mut sample = vec![headers.clone()];
+                while sample.len() < self.infer_schema_length {
+                    match rows.next_row()? {
+                        Some(row) => sample.push(row),
+                        None => break,
+                    }
+                }
+
+                Ok(infer_field_schema(&headers, &sample))
+            },
+        )
+    }
+}
+
+/// A worksheet to scan, the first one of the file when no name is given
+#[derive(Debug, Clone)]
+struct SheetSource {
+    file: String,
+    sheet_name: Option<String>,
+}
+
+/// The matching worksheets of one or more workbooks. Each sheet is scanned as its own
+/// partition that parses only the projected columns and stops once LIMIT is reached.
+#[derive(Debug)]
+pub struct ExcelTable {
+    sources: Vec<SheetSource>,
+    layout: SheetLayout,
+    schema: SchemaRef,
+    /// Number of worksheet columns, the virtual columns follow them in the schema
+    data_columns: usize,
+    batch_size: usize,
+}
+
+#[async_trait]
+impl TableProvider for ExcelTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let projection = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..self.schema.fields().len()).collect(),
+        };
+        let schema = Arc::new(self.schema.project(&projection)?);
+
+        let partitions = self
+            .sources
+            .iter()
+            .map(|source| {
+                Arc::new(ExcelPartition {
+                    source: source.clone(),
+                    layout: self.layout.clone(),
+                    schema: schema.clone(),
+                    projection: projection.clone(),
+                    data_columns: self.data_columns,
+                    batch_size: self.batch_size,
+                    limit,
+                }) as Arc<dyn PartitionStream>
+            })
+            .collect();
+
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            partitions,
+            None,
+            Vec::<LexOrdering>::new(),
+            false,
+            limit,
+        )?))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ExcelPartition {
+    source: SheetSource,
+    layout: SheetLayout,
+    /// Schema of the projected columns
+    schema: SchemaRef,
+    /// Positions of the projected columns in the table schema
+    projection: Vec<usize>,
+    data_columns: usize,
+    batch_size: usize,
+    limit: Option<usize>,
+}
+
+impl PartitionStream for ExcelPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.schema.clone(), 2);
+        let tx = builder.tx();
+        let partition = self.clone();
+
+        // Sending fails once the consumer has dropped the stream, e.g. when a LIMIT
+        // above the scan is satisfied, which stops the parsing as well
+        builder.spawn_blocking(move || {
+            partition
+                .read(|batch| tx.blocking_send(Ok(batch)).is_ok())
+                .map_err(|e| DataFusionError::External(Box::new(e)))
+        });
+        builder.build()
+    }
+}
+
+impl ExcelPartition {
+    fn read<F>(&self, mut emit: F) -> AppResult<()>
+    where
+        F: FnMut(RecordBatch) -> bool,
+    {
+        with_sheet_rows(
+            &self.source.file,
+            self.source.sheet_name.as_deref(),
+            &self.layout,
+            |headers, rows| {
+                let names = header_names(&headers);
+                let columns = self
+                    .projection
+                    .iter()
+                    .zip(self.schema.fields())
+                    .map(|(index, field)| self.column_source(*index, field.name(), &names))
+                    .collect::<Vec<_>>();
+                let mut builders = self
+                    .schema
+                    .fields()
+                    .iter()
+                    .map(|f| ColumnBuilder::new(f.data_type(), self.batch_size))
+                    .collect::<Vec<_>>();
+                let mut remaining = self.limit.unwrap_or(usize::MAX);
+                let mut row_count = 0;
+
+                while remaining > 0 {
+                    let Some(row) = rows.next_row()? else {
+                        break;
                     };
-                    arrays.push(array);
+                    for (builder, column) in builders.iter_mut().zip(&columns) {
+                        builder.append(match column {
+                            ColumnSource::Cell(index) => row.get(*index).unwrap_or(&Data::Empty),
+                            ColumnSource::Constant(value) => value,
+                        });
+                    }
+                    row_count += 1;
+                    remaining -= 1;
+
+                    if row_count == self.batch_size {
+                        if !emit(self.finish_batch(&mut builders, row_count)?) {
+                            return Ok(());
+                        }
+                        row_count = 0;
+                    }
                 }
+                if row_count > 0 {
+                    emit(self.finish_batch(&mut builders, row_count)?);
+                }
+                Ok(())
+            },
+        )
+    }
 
-                let record_batch = RecordBatch::try_new(Arc::new(schema), arrays)?;
-                Ok(record_batch)
-            }
-            None => Err(AppError::BadRequest {
-                message: "Header not found".to_string(),
-            }),
+    /// Finds a table column in this sheet, by name since the sheets of a union may
+    /// order their columns differently
+    fn column_source(&self, index: usize, name: &str, names: &[String]) -> ColumnSource {
+        if index >= self.data_columns {
+            let value = match name {
+                SHEET_COLUMN => self.source.sheet_name.clone().unwrap_or_default(),
+                _ => self.source.file.clone(),
+            };
+            return ColumnSource::Constant(Data::String(value));
+        }
+
+        if names.get(index).is_some_and(|n| n == name) {
+            return ColumnSource::Cell(index);
+        }
+        match names.iter().position(|n| n == name) {
+            Some(position) => ColumnSource::Cell(position),
+            // Columns missing from this sheet are NULL
+            None => ColumnSource::Constant(Data::Empty),
+        }
+    }
+
+    fn finish_batch(
+        &self,
+        builders: &mut [ColumnBuilder],
+        row_count: usize,
+    ) -> AppResult<RecordBatch> {
+        let columns = builders.iter_mut().map(|b| b.finish()).collect();
+        // count(*) projects no column at all, only the number of rows
+        let options = RecordBatchOptions::new().with_row_count(Some(row_count));
+        Ok(RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            columns,
+            &options,
+        )?)
+    }
+}
+
+enum ColumnSource {
+    /// Position of the column in the sheet
+    Cell(usize),
+    /// Same value on every row
+    Constant(Data),
+}
+
+/// Opens the worksheet (the first one when no name is given) and hands its header and
+/// data rows, as laid out by `layout`, to `f`.
+/// The workbook format (xlsx, xlsm, xlsb, xls or ods) is detected from the extension.
+fn with_sheet_rows<T, F>(
+    file: &str,
+    sheet_name: Option<&str>,
+    layout: &SheetLayout,
+    f: F,
+) -> AppResult<T>
+where
+    F: FnOnce(Vec<Data>, &mut DataRows<'_, '_>) -> AppResult<T>,
+{
+    let mut workbook = open_workbook_auto(file)?;
+    let sheet_name = match sheet_name {
+        Some(sheet_name) => sheet_name.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_SHEET.to_string()),
+    };
+
+    match workbook {
+        Sheets::Xlsx(mut xlsx) => {
+            let mut cells = xlsx.worksheet_cells_reader(&sheet_name)?;
+            let dimensions = cells.dimensions();
+            let next_cell: NextCell = Box::new(move || {
+                Ok(cells.next_cell()?.map(|cell| {
+                    let (row, column) = cell.get_position();
+                    (row, column, Data::from(cell.get_value().clone()))
+                }))
+            });
+            layout.read(
+                SheetRows::new(dimensions.start.1, dimensions.end.1, next_cell),
+                f,
+            )
+        }
+        // The other formats have no cell reader, so the worksheet is loaded as a whole
+        mut workbook => {
+            let range = workbook.worksheet_range(&sheet_name)?;
+            let (start_row, start_column) = range.start().unwrap_or_default();
+            let (_, end_column) = range.end().unwrap_or_default();
+            let mut cells = range.used_cells();
+            let next_cell: NextCell = Box::new(move || {
+                Ok(cells.next().map(|(row, column, value)| {
+                    (
+                        start_row + row as u32,
+                        start_column + column as u32,
+                        value.clone(),
+                    )
+                }))
+            });
+            layout.read(SheetRows::new(start_column, end_column, next_cell), f)
         }
     }
 }
 
-impl Default for ExcelParseOptions {
+/// Where the header and the data are found in a worksheet
+#[derive(Debug, Clone)]
+struct SheetLayout {
+    range: Option<CellRange>,
+    header_row: usize,
+    has_header: bool,
+    skip_rows: usize,
+    skip_footer: usize,
+}
+
+impl Default for SheetLayout {
     fn default() -> Self {
-        Self {}
+        Self {
+            range: None,
+            header_row: 1,
+            has_header: true,
+            skip_rows: 0,
+            skip_footer: 0,
+        }
     }
 }
 
-pub fn infer_field_schema(range: &Range<Data>, infer_schema_length: usize) -> AppResult<Schema> {
-    let headers: Vec<String> = if range.headers().is_none() {
-        if let Some(rows) = range.rows().next() {
-            rows.iter()
-                .enumerate()
-                .map(|(i, _)| format!("t{}", i + 1))
-                .collect()
-        } else {
-            return Err(AppError::BadRequest {
+impl SheetLayout {
+    fn read<T, F>(&self, mut rows: SheetRows<'_>, f: F) -> AppResult<T>
+    where
+        F: FnOnce(Vec<Data>, &mut DataRows<'_, '_>) -> AppResult<T>,
+    {
+        if let Some(range) = &self.range {
+            rows.restrict(range);
+        }
+
+        for _ in 1..self.header_row {
+            rows.next_row()?;
+        }
+        let headers = if self.has_header {
+            rows.next_row()?.ok_or_else(|| AppError::BadRequest {
                 message: "Header not found".to_string(),
-            });
+            })?
+        } else {
+            // Empty headers get the names t1..tn
+            vec![Data::Empty; rows.width]
+        };
+        for _ in 0..self.skip_rows {
+            rows.next_row()?;
         }
-    } else {
-        range
-            .headers()
-            .unwrap()
-            .iter()
-            .map(|h| h.to_string())
-            .collect()
-    };
+
+        f(headers, &mut DataRows::new(&mut rows, self.skip_footer))
+    }
+}
+
+/// A rectangular block of cells such as `B3:H200`
+#[derive(Debug, Clone, Copy)]
+pub struct CellRange {
+    /// 0-based (row, column) of the top left cell
+    start: (u32, u32),
+    /// 0-based (row, column) of the bottom right cell
+    end: (u32, u32),
+}
+
+impl CellRange {
+    pub fn parse(value: &str) -> AppResult<Self> {
+        let invalid = || AppError::BadRequest {
+            message: format!("Invalid range '{}', expected e.g. 'B3:H200'", value),
+        };
+
+        let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+        let start = parse_cell_ref(start.trim()).ok_or_else(invalid)?;
+        let end = parse_cell_ref(end.trim()).ok_or_else(invalid)?;
+        if start.0 > end.0 || start.1 > end.1 {
+            return Err(invalid());
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+/// Parses an A1 style reference such as `B3` into a 0-based (row, column)
+fn parse_cell_ref(value: &str) -> Option<(u32, u32)> {
+    let value = value.replace('$', "");
+    let (letters, digits) = value.split_at(value.find(|c: char| c.is_ascii_digit())?);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let column = letters.chars().try_fold(0u32, |column, c| {
+        column
+            .checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    })?;
+    let row: u32 = digits.parse().ok()?;
+    if row == 0 {
+        return None;
+    }
+
+    Some((row - 1, column - 1))
+}
+
+/// Yields the non-empty cells of a worksheet in row order, as (row, column, value)
+type NextCell<'a> = Box<dyn FnMut() -> AppResult<Option<(u32, u32, Data)>> + 'a>;
+
+/// Rows of a worksheet, assembled from calamine's cell stream so the sheet is parsed
+/// only as far as it is read. Rows start at the first row of the sheet and columns
+/// at the first used column, like calamine's own ranges.
+struct SheetRows<'a> {
+    next_cell: NextCell<'a>,
+    start_column: u32,
+    width: usize,
+    next_row: u32,
+    /// Last row and column of the requested range, if any
+    end: Option<(u32, u32)>,
+    /// First cell of a later row, read while completing the previous one
+    pending: Option<(u32, u32, Data)>,
+    exhausted: bool,
+}
+
+impl<'a> SheetRows<'a> {
+    fn new(start_column: u32, end_column: u32, next_cell: NextCell<'a>) -> Self {
+        Self {
+            next_cell,
+            start_column,
+            width: (end_column.saturating_sub(start_column) + 1) as usize,
+            next_row: 0,
+            end: None,
+            pending: None,
+            exhausted: false,
+        }
+    }
+
+    /// Limits the rows and columns to the range, cells outside of it are skipped
+    fn restrict(&mut self, range: &CellRange) {
+        self.next_row = range.start.0;
+        self.start_column = range.start.1;
+        self.width = (range.end.1 - range.start.1 + 1) as usize;
+        self.end = Some(range.end);
+    }
+
+    fn next_row(&mut self) -> AppResult<Option<Vec<Data>>> {
+        if self.exhausted && self.pending.is_none() {
+            return Ok(None);
+        }
+        if self.end.is_some_and(|(end_row, _)| self.next_row > end_row) {
+            return Ok(None);
+        }
+
+        let mut row = vec![Data::Empty; self.width];
+        loop {
+            let (row_index, column, value) = match self.pending.take() {
+                Some(cell) => cell,
+                None => match (self.next_cell)()? {
+                    Some(cell) => cell,
+                    None => {
+                        self.exhausted = true;
+                        break;
+                    }
+                },
+            };
+
+            // Rows without any cell come out empty
+            if row_index > self.next_row {
+                self.pending = Some((row_index, column, value));
+                break;
+            }
+            let in_range = column >= self.start_column
+                && self.end.is_none_or(|(_, end_column)| column <= end_column);
+            if row_index == self.next_row && in_range {
+                let index = (column - self.start_column) as usize;
+                if index >= row.len() {
+                    row.resize(index + 1, Data::Empty);
+                }
+                row[index] = value;
+            }
+        }
+
+        if self.exhausted && self.pending.is_none() && row.iter().all(|c| *c == Data::Empty) {
+            return Ok(None);
+        }
+        self.next_row += 1;
+        Ok(Some(row))
+    }
+}
+
+/// The data rows below the header, holding back the last `skip_footer` rows so they
+/// are never returned
+struct DataRows<'s, 'a> {
+    rows: &'s mut SheetRows<'a>,
+    footer: VecDeque<Vec<Data>>,
+    skip_footer: usize,
+}
+
+impl<'s, 'a> DataRows<'s, 'a> {
+    fn new(rows: &'s mut SheetRows<'a>, skip_footer: usize) -> Self {
+        Self {
+            rows,
+            footer: VecDeque::with_capacity(skip_footer + 1),
+            skip_footer,
+        }
+    }
+
+    fn next_row(&mut self) -> AppResult<Option<Vec<Data>>> {
+        while self.footer.len() <= self.skip_footer {
+            match self.rows.next_row()? {
+                Some(row) => self.footer.push_back(row),
+                None => return Ok(None),
+            }
+        }
+        Ok(self.footer.pop_front())
+    }
+}
+
+enum ColumnBuilder {
+    Int(Int64Builder),
+    Float(Float64Builder),
+    Timestamp(TimestampNanosecondBuilder),
+    Text(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType, capacity: usize) -> Self {
+        match data_type {
+            DataType::Int64 => ColumnBuilder::Int(Int64Builder::with_capacity(capacity)),
+            DataType::Float64 => ColumnBuilder::Float(Float64Builder::with_capacity(capacity)),
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                ColumnBuilder::Timestamp(TimestampNanosecondBuilder::with_capacity(capacity))
+            }
+            _ => ColumnBuilder::Text(StringBuilder::new()),
+        }
+    }
+
+    /// Cells that don't fit the inferred column type become NULL
+    fn append(&mut self, cell: &Data) {
+        match self {
+            ColumnBuilder::Int(builder) => builder.append_option(match cell {
+                Data::Int(v) => Some(*v),
+                Data::Float(v) => Some(*v as i64),
+                _ => None,
+            }),
+            ColumnBuilder::Float(builder) => builder.append_option(match cell {
+                Data::Float(v) => Some(*v),
+                Data::Int(v) => Some(*v as f64),
+                _ => None,
+            }),
+            ColumnBuilder::Timestamp(builder) => builder.append_option(
+                cell_datetime(cell).and_then(|dt| dt.and_utc().timestamp_nanos_opt()),
+            ),
+            ColumnBuilder::Text(builder) => match cell {
+                Data::Empty => builder.append_null(),
+                Data::String(s) => builder.append_value(s),
+                _ => builder.append_value(cell.to_string()),
+            },
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Int(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Float(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Timestamp(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Text(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Date and time cells as a timestamp. ODS dates arrive as ISO 8601
+/// text, which may be a plain date, and durations are counted from the Excel epoch
+/// like the numeric xlsx ones.
+fn cell_datetime(cell: &Data) -> Option<chrono::NaiveDateTime> {
+    match cell {
+        Data::DateTime(dt) => dt.as_datetime(),
+        Data::DateTimeIso(_) => cell.as_datetime().or_else(|| {
+            cell.as_date()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN))
+        }),
+        Data::DurationIso(_) => cell.as_duration().and_then(|duration| {
+            Data::Float(duration.num_milliseconds() as f64 / 86_400_000.0).as_datetime()
+        }),
+        _ => None,
+    }
+}
+
+/// Infers the column types from the sampled rows (the header row included).
+/// Headers are taken from the first row, or named t1..tn when it is empty.
+pub fn infer_field_schema(headers: &[Data], rows: &[Vec<Data>]) -> Schema {
+    let headers = header_names(headers);
 
     let num_columns = headers.len();
     let mut data_types: Vec<HashSet<DataType>> = vec![HashSet::new(); num_columns];
 
-    for row in range.rows().take(infer_schema_length) {
+    for row in rows {
         for (i, cell) in row.iter().enumerate() {
             if i < num_columns && !matches!(cell, Data::Empty) {
-                let inferred_type = infer_cell_data_type(cell);
-                data_types[i].insert(inferred_type);
+                data_types[i].insert(infer_cell_data_type(cell));
             }
         }
     }
@@ -212,40 +743,71 @@ pub fn infer_field_schema(range: &Range<Data>, infer_schema_length: usize) -> Ap
     let fields: Vec<Field> = data_types
         .iter()
         .enumerate()
-        .map(|(i, types)| {
-            let data_type = if types.is_empty() {
-                DataType::Utf8
-            } else if types.contains(&DataType::Int64) {
-                DataType::Int64
-            } else if types.contains(&DataType::Float64) {
-                DataType::Float64
-            } else if types.contains(&DataType::Timestamp(TimeUnit::Nanosecond, None)) {
-                DataType::Timestamp(TimeUnit::Nanosecond, None)
-            } else {
-                DataType::Utf8
-            };
-            Field::new(headers[i].clone(), data_type, true)
-        })
+        .map(|(i, types)| Field::new(headers[i].clone(), merge_data_types(types), true))
         .collect();
 
-    Ok(Schema::new(fields))
+    Schema::new(fields)
 }
 
-fn infer_cell_data_type(cell: &Data) -> DataType {
-    match cell {
-        Data::Int(_) => DataType::Int64,
-        Data::Float(_) => DataType::Float64,
-        Data::DateTime(_) => DataType::Timestamp(TimeUnit::Nanosecond, None),
-        _ => DataType::Utf8,
+fn header_names(headers: &[Data]) -> Vec<String> {
+    if headers.iter().all(|h| *h == Data::Empty) {
+        (0..headers.len()).map(|i| format!("t{}", i + 1)).collect()
+    } else {
+        headers.iter().map(|h| h.to_string()).collect()
     }
 }
 
-fn convert_arrow_data_type(cell: &Data) -> DataType {
+/// Merges the columns of several sheets by name, in the order they first appear
+fn unify_schemas(schemas: &[Schema]) -> Schema {
+    let mut names: Vec<String> = Vec::new();
+    let mut data_types: HashMap<String, HashSet<DataType>> = HashMap::new();
+
+    for schema in schemas {
+        for field in schema.fields() {
+            data_types
+                .entry(field.name().clone())
+                .or_insert_with(|| {
+                    names.push(field.name().clone());
+                    HashSet::new()
+                })
+                .insert(field.data_type().clone());
+        }
+    }
+
+    let fields: Vec<Field> = names
+        .iter()
+        .map(|name| Field::new(name.clone(), merge_data_types(&data_types[name]), true))
+        .collect();
+
+    Schema::new(fields)
+}
+
+fn merge_data_types(types: &HashSet<DataType>) -> DataType {
+    if types.is_empty() {
+        DataType::Utf8
+    } else if types.contains(&DataType::Int64) {
+        DataType::Int64
+    } else if types.contains(&DataType::Float64) {
+        DataType::Float64
+    } else if types.contains(&DataType::Timestamp(TimeUnit::Nanosecond, None)) {
+        DataType::Timestamp(TimeUnit::Nanosecond, None)
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Whether a path or sheet name contains glob wildcards
+fn is_pattern(value: &str) -> bool {
+    value.contains(['*', '?', '['])
+}
+
+fn infer_cell_data_type(cell: &Data) -> DataType {
     match cell {
-        Data::String(_) => DataType::Utf8,
-        Data::Float(_) => DataType::Float64,
         Data::Int(_) => DataType::Int64,
-        Data::DateTime(_) => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        Data::Float(_) => DataType::Float64,
+        Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => {
+            DataType::Timestamp(TimeUnit::Nanosecond, None)
+        }
         _ => DataType::Utf8,
     }
 }
@@ -0,0 +1,131 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use crate::utils::file_utils::find_files;
+use datafusion::arrow::json::reader::infer_json_schema_from_iterator;
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::arrow::record_batch::RecordBatch;
+use serde_json::Value;
+use std::fs;
+use std::sync::Arc;
+
+pub struct JsonReader {
+    path: String,
+    records_path: Option<String>,
+    infer_schema_length: usize,
+}
+
+impl JsonReader {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            records_path: None,
+            infer_schema_length: 1000,
+        }
+    }
+
+    pub fn with_records_path(mut self, records_path: String) -> Self {
+        self.records_path = Some(records_path);
+        self
+    }
+
+    pub fn with_infer_schema_length(mut self, infer_schema_length: usize) -> Self {
+        self.infer_schema_length = infer_schema_length;
+        self
+    }
+
+    pub fn finish(self) -> AppResult<RecordBatch> {
+        let mut records: Vec<Value> = Vec::new();
+
+        let files = find_files(&self.path)?;
+        for file in files {
+            let document: Value = serde_json::from_str(&fs::read_to_string(&file)?)?;
+            let selected = match self.records_path {
+                Some(ref records_path) => select_records(document, records_path)?,
+                None => document,
+            };
+
+            match selected {
+                Value::Array(values) => records.extend(values),
+                Value::Object(_) => records.push(selected),
+                Value::Null => {}
+                _ => {
+                    return Err(AppError::BadRequest {
+                        message: format!(
+                            "'{}' must contain a JSON object or an array of objects",
+                            file
+                        ),
+                    })
+                }
+            }
+        }
+
+        // A length of 0 means "look at every record", which keeps late-appearing keys.
+        let infer_length = match self.infer_schema_length {
+            0 => records.len(),
+            length => length,
+        };
+        let schema = Arc::new(infer_json_schema_from_iterator(
+            records.iter().take(infer_length).map(Ok),
+        )?);
+
+        if schema.fields().is_empty() {
+            return Err(AppError::BadRequest {
+                message: "No columns found in JSON records".to_string(),
+            });
+        }
+
+        let mut decoder = ReaderBuilder::new(schema.clone()).build_decoder()?;
+        decoder.serialize(&records)?;
+
+        match decoder.flush()? {
+            Some(record_batch) => Ok(record_batch),
+            None => Ok(RecordBatch::new_empty(schema)),
+        }
+    }
+}
+
+/// Walks a JSONPath-like selector such as `$.data.items` or `$.pages[0].rows`
+fn select_records(mut document: Value, records_path: &str) -> AppResult<Value> {
+    let invalid = || AppError::BadRequest {
+        message: format!("Invalid records_path: '{}'", records_path),
+    };
+
+    let path = records_path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(position) => (&segment[..position], &segment[position..]),
+            None => (segment, ""),
+        };
+
+        if !key.is_empty() {
+            document = match document {
+                Value::Object(mut map) => map.remove(key).ok_or_else(|| AppError::BadRequest {
+                    message: format!("Key '{}' not found in records_path '{}'", key, records_path),
+                })?,
+                _ => return Err(invalid()),
+            };
+        }
+
+        for index in indexes.split_terminator(']') {
+            let index: usize = index
+                .strip_prefix('[')
+                .and_then(|i| i.trim().parse().ok())
+                .ok_or_else(invalid)?;
+            document = match document {
+                Value::Array(mut values) if index < values.len() => values.swap_remove(index),
+                _ => {
+                    return Err(AppError::BadRequest {
+                        message: format!(
+                            "Index {} out of range in records_path '{}'",
+                            index, records_path
+                        ),
+                    })
+                }
+            };
+        }
+    }
+
+    Ok(document)
+}
@@ -1 +1,3 @@
 pub mod excel;
+pub mod json;
+pub mod sqlite;
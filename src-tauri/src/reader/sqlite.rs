@@ -0,0 +1,405 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use async_trait::async_trait;
+use datafusion::arrow::array::{
+    ArrayRef, BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use datafusion::catalog::Session;
+use datafusion::common::ScalarValue;
+use datafusion::datasource::TableProvider;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::expr::InList;
+use datafusion::logical_expr::{
+    Between, BinaryExpr, Expr, Operator, TableProviderFilterPushDown, TableType,
+};
+use datafusion::physical_expr::LexOrdering;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::streaming::{PartitionStream, StreamingTableExec};
+use datafusion::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::any::Any;
+use std::sync::Arc;
+
+const BATCH_SIZE: usize = 8192;
+
+/// A table of a local SQLite database file. Projections, simple filters and LIMIT
+/// are translated to SQL, so SQLite only returns the rows and columns the query needs.
+/// Rows are streamed while the plan runs.
+#[derive(Debug)]
+pub struct SqliteTable {
+    path: String,
+    table: String,
+    schema: SchemaRef,
+}
+
+impl SqliteTable {
+    pub fn try_new(path: String, table: String) -> AppResult<Self> {
+        let conn = open_read_only(&path)?;
+        let mut statement = conn.prepare("SELECT name, type FROM pragma_table_info(?1)")?;
+        let fields = statement
+            .query_map([&table], |row| {
+                let name: String = row.get(0)?;
+                let declared_type: String = row.get(1)?;
+                Ok(Field::new(name, column_type(&declared_type), true))
+            })?
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+        if fields.is_empty() {
+            return Err(AppError::BadRequest {
+                message: format!("Table '{}' not found in '{}'", table, path),
+            });
+        }
+
+        Ok(Self {
+            path,
+            table,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for SqliteTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = match projection {
+            Some(projection) => Arc::new(self.schema.project(projection)?),
+            None => self.schema.clone(),
+        };
+
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| quote_identifier(f.name()))
+            .collect::<Vec<_>>();
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            // count(*) needs no column, only the number of rows
+            if columns.is_empty() {
+                "1".to_string()
+            } else {
+                columns.join(", ")
+            },
+            quote_identifier(&self.table)
+        );
+        let conditions = filters.iter().filter_map(filter_to_sql).collect::<Vec<_>>();
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        if let Some(limit) = limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let partition = SqlitePartition {
+            path: self.path.clone(),
+            sql,
+            schema: schema.clone(),
+        };
+
+        // The query only runs once the plan is executed, not while it is built
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![Arc::new(partition) as Arc<dyn PartitionStream>],
+            None,
+            Vec::<LexOrdering>::new(),
+            false,
+            limit,
+        )?))
+    }
+
+    /// SQLite compares the stored values, which can differ from the converted ones
+    /// DataFusion sees (lossy casts into the column type, untyped columns, collations),
+    /// so DataFusion applies pushed down filters again
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|filter| match filter_to_sql(filter) {
+                Some(_) => TableProviderFilterPushDown::Inexact,
+                None => TableProviderFilterPushDown::Unsupported,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug)]
+struct SqlitePartition {
+    path: String,
+    sql: String,
+    /// Schema of the projected columns
+    schema: SchemaRef,
+}
+
+impl PartitionStream for SqlitePartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.schema.clone(), 2);
+        let tx = builder.tx();
+        let (path, sql, schema) = (self.path.clone(), self.sql.clone(), self.schema.clone());
+
+        // Sending fails once the consumer has dropped the stream, which stops the read
+        builder.spawn_blocking(move || {
+            read_batches(&path, &sql, schema, |batch| {
+                tx.blocking_send(Ok(batch)).is_ok()
+            })
+            .map_err(|e| DataFusionError::External(Box::new(e)))
+        });
+        builder.build()
+    }
+}
+
+fn open_read_only(path: &str) -> AppResult<Connection> {
+    Ok(Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?)
+}
+
+/// Maps a declared column type to Arrow following SQLite's type affinity rules
+fn column_type(declared_type: &str) -> DataType {
+    let declared_type = declared_type.to_uppercase();
+    if declared_type.contains("INT") {
+        DataType::Int64
+    } else if declared_type.contains("CHAR")
+        || declared_type.contains("CLOB")
+        || declared_type.contains("TEXT")
+    {
+        DataType::Utf8
+    } else if declared_type.contains("BLOB") {
+        DataType::Binary
+    } else if declared_type.contains("BOOL") {
+        DataType::Boolean
+    } else if declared_type.contains("DATE") || declared_type.contains("TIME") {
+        // Dates are stored as text (or numbers) and are left for the query to parse
+        DataType::Utf8
+    } else if declared_type.is_empty() {
+        DataType::Utf8
+    } else {
+        DataType::Float64
+    }
+}
+
+enum ColumnBuilder {
+    Int(Int64Builder),
+    Float(Float64Builder),
+    Boolean(BooleanBuilder),
+    Text(StringBuilder),
+    Binary(BinaryBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Int64 => ColumnBuilder::Int(Int64Builder::with_capacity(BATCH_SIZE)),
+            DataType::Float64 => ColumnBuilder::Float(Float64Builder::with_capacity(BATCH_SIZE)),
+            DataType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::with_capacity(BATCH_SIZE)),
+            DataType::Binary => ColumnBuilder::Binary(BinaryBuilder::new()),
+            _ => ColumnBuilder::Text(StringBuilder::new()),
+        }
+    }
+
+    /// SQLite columns are dynamically typed, so values are converted leniently and
+    /// anything that does not fit the column type becomes NULL
+    fn append(&mut self, value: ValueRef) {
+        match self {
+            ColumnBuilder::Int(builder) => builder.append_option(match value {
+                ValueRef::Integer(v) => Some(v),
+                ValueRef::Real(v) => Some(v as i64),
+                ValueRef::Text(v) => std::str::from_utf8(v).ok().and_then(|v| v.parse().ok()),
+                _ => None,
+            }),
+            ColumnBuilder::Float(builder) => builder.append_option(match value {
+                ValueRef::Integer(v) => Some(v as f64),
+                ValueRef::Real(v) => Some(v),
+                ValueRef::Text(v) => std::str::from_utf8(v).ok().and_then(|v| v.parse().ok()),
+                _ => None,
+            }),
+            ColumnBuilder::Boolean(builder) => builder.append_option(match value {
+                ValueRef::Integer(v) => Some(v != 0),
+                ValueRef::Real(v) => Some(v != 0.0),
+                ValueRef::Text(v) => match v.to_ascii_lowercase().as_slice() {
+                    b"true" | b"1" => Some(true),
+                    b"false" | b"0" => Some(false),
+                    _ => None,
+                },
+                _ => None,
+            }),
+            ColumnBuilder::Text(builder) => match value {
+                ValueRef::Null => builder.append_null(),
+                ValueRef::Integer(v) => builder.append_value(v.to_string()),
+                ValueRef::Real(v) => builder.append_value(v.to_string()),
+                ValueRef::Text(v) | ValueRef::Blob(v) => {
+                    builder.append_value(String::from_utf8_lossy(v))
+                }
+            },
+            ColumnBuilder::Binary(builder) => match value {
+                ValueRef::Null => builder.append_null(),
+                ValueRef::Integer(v) => builder.append_value(v.to_string()),
+                ValueRef::Real(v) => builder.append_value(v.to_string()),
+                ValueRef::Text(v) | ValueRef::Blob(v) => builder.append_value(v),
+            },
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Int(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Float(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Boolean(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Text(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Binary(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Runs the query and hands its rows to `emit` in batches, until `emit` returns false
+fn read_batches<F>(path: &str, sql: &str, schema: SchemaRef, mut emit: F) -> AppResult<()>
+where
+    F: FnMut(RecordBatch) -> bool,
+{
+    let conn = open_read_only(path)?;
+    let mut statement = conn.prepare(sql)?;
+    let mut rows = statement.query([])?;
+
+    let mut builders = schema
+        .fields()
+        .iter()
+        .map(|f| ColumnBuilder::new(f.data_type()))
+        .collect::<Vec<_>>();
+    let mut row_count = 0;
+
+    let mut flush = |builders: &mut Vec<ColumnBuilder>, row_count: usize| -> AppResult<bool> {
+        let columns = builders.iter_mut().map(|b| b.finish()).collect();
+        let options = RecordBatchOptions::new().with_row_count(Some(row_count));
+        Ok(emit(RecordBatch::try_new_with_options(
+            schema.clone(),
+            columns,
+            &options,
+        )?))
+    };
+
+    while let Some(row) = rows.next()? {
+        for (index, builder) in builders.iter_mut().enumerate() {
+            builder.append(row.get_ref(index)?);
+        }
+        row_count += 1;
+        if row_count == BATCH_SIZE {
+            if !flush(&mut builders, row_count)? {
+                return Ok(());
+            }
+            row_count = 0;
+        }
+    }
+    if row_count > 0 {
+        flush(&mut builders, row_count)?;
+    }
+
+    Ok(())
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Translates a filter to a SQLite condition, or None when it cannot be evaluated
+/// by SQLite with the same result
+fn filter_to_sql(filter: &Expr) -> Option<String> {
+    match filter {
+        Expr::Column(column) => Some(quote_identifier(&column.name)),
+        Expr::Literal(value, _) => literal_to_sql(value),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let op = match op {
+                Operator::Eq => "=",
+                Operator::NotEq => "<>",
+                Operator::Lt => "<",
+                Operator::LtEq => "<=",
+                Operator::Gt => ">",
+                Operator::GtEq => ">=",
+                Operator::And => "AND",
+                Operator::Or => "OR",
+                _ => return None,
+            };
+            Some(format!(
+                "({} {} {})",
+                filter_to_sql(left)?,
+                op,
+                filter_to_sql(right)?
+            ))
+        }
+        Expr::Not(expr) => Some(format!("(NOT {})", filter_to_sql(expr)?)),
+        Expr::IsNull(expr) => Some(format!("({} IS NULL)", filter_to_sql(expr)?)),
+        Expr::IsNotNull(expr) => Some(format!("({} IS NOT NULL)", filter_to_sql(expr)?)),
+        Expr::InList(InList {
+            expr,
+            list,
+            negated,
+        }) => {
+            let values = list.iter().map(filter_to_sql).collect::<Option<Vec<_>>>()?;
+            Some(format!(
+                "({} {}IN ({}))",
+                filter_to_sql(expr)?,
+                if *negated { "NOT " } else { "" },
+                values.join(", ")
+            ))
+        }
+        Expr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => Some(format!(
+            "({} {}BETWEEN {} AND {})",
+            filter_to_sql(expr)?,
+            if *negated { "NOT " } else { "" },
+            filter_to_sql(low)?,
+            filter_to_sql(high)?
+        )),
+        _ => None,
+    }
+}
+
+fn literal_to_sql(value: &ScalarValue) -> Option<String> {
+    match value {
+        ScalarValue::Int8(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int16(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int32(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int64(Some(v)) => Some(v.to_string()),
+        ScalarValue::UInt8(Some(v)) => Some(v.to_string()),
+        ScalarValue::UInt16(Some(v)) => Some(v.to_string()),
+        ScalarValue::UInt32(Some(v)) => Some(v.to_string()),
+        ScalarValue::UInt64(Some(v)) if *v <= i64::MAX as u64 => Some(v.to_string()),
+        ScalarValue::Float32(Some(v)) if v.is_finite() => Some(format!("{:?}", v)),
+        ScalarValue::Float64(Some(v)) if v.is_finite() => Some(format!("{:?}", v)),
+        ScalarValue::Boolean(Some(v)) => Some(if *v { "1" } else { "0" }.to_string()),
+        ScalarValue::Utf8(Some(v))
+        | ScalarValue::LargeUtf8(Some(v))
+        | ScalarValue::Utf8View(Some(v)) => Some(format!("'{}'", v.replace('\'', "''"))),
+        _ => None,
+    }
+}
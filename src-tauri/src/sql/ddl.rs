@@ -0,0 +1,77 @@
+use crate::context::schema::AppResult;
+use crate::sql::dialect::SqlDialect;
+use datafusion::arrow::array::{Array, AsArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Int64Type};
+use datafusion::dataframe::DataFrame;
+use datafusion::functions::expr_fn::character_length;
+use datafusion::functions_aggregate::expr_fn::max;
+use datafusion::prelude::ident;
+use std::io::Write;
+
+/// Writes a CREATE TABLE statement derived from the DataFrame's Arrow schema.
+/// String columns are sized from the longest value in the result, so the query runs
+/// once more to measure them.
+pub async fn generate_create_table<W: Write>(
+    df: &DataFrame,
+    table_name: &str,
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+) -> AppResult<()> {
+    let widths = max_widths(df).await?;
+
+    let columns = df
+        .schema()
+        .fields()
+        .iter()
+        .zip(widths)
+        .map(|(field, width)| {
+            (
+                db_dialect.quote_identifier(field.name()),
+                db_dialect.column_type(field.data_type(), width),
+                field.is_nullable(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    db_dialect.write_create_table(out, &db_dialect.quote_table_name(table_name), &columns)
+}
+
+/// Longest value, in characters, of each string column
+async fn max_widths(df: &DataFrame) -> AppResult<Vec<Option<usize>>> {
+    let fields = df.schema().fields();
+    let string_columns = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            matches!(
+                field.data_type(),
+                DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+            )
+        })
+        .map(|(index, field)| (index, field.name().clone()))
+        .collect::<Vec<_>>();
+
+    let mut widths = vec![None; fields.len()];
+    if string_columns.is_empty() {
+        return Ok(widths);
+    }
+
+    let aggregates = string_columns
+        .iter()
+        .map(|(_, name)| max(character_length(ident(name))))
+        .collect::<Vec<_>>();
+    let batches = df.clone().aggregate(vec![], aggregates)?.collect().await?;
+
+    for batch in batches.iter().filter(|b| b.num_rows() > 0) {
+        for ((index, _), column) in string_columns.iter().zip(batch.columns()) {
+            let lengths = cast(column, &DataType::Int64)?;
+            let lengths = lengths.as_primitive::<Int64Type>();
+            if !lengths.is_null(0) {
+                widths[*index] = Some(lengths.value(0) as usize);
+            }
+        }
+    }
+
+    Ok(widths)
+}
@@ -0,0 +1,950 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Database specific syntax used when generating SQL scripts from query results
+pub trait SqlDialect: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Column type for an Arrow type; `max_width` is the longest observed string, if known
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean => "BOOLEAN".to_string(),
+            DataType::Int8 | DataType::Int16 | DataType::UInt8 => "SMALLINT".to_string(),
+            DataType::Int32 | DataType::UInt16 => "INTEGER".to_string(),
+            DataType::Int64 | DataType::UInt32 => "BIGINT".to_string(),
+            DataType::UInt64 => "NUMERIC(20, 0)".to_string(),
+            DataType::Float16 | DataType::Float32 => "REAL".to_string(),
+            DataType::Float64 => "DOUBLE PRECISION".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                format!("NUMERIC({}, {})", precision, scale)
+            }
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => match max_width {
+                Some(width) if width <= 10_485_760 => format!("VARCHAR({})", width.max(1)),
+                _ => "TEXT".to_string(),
+            },
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => "BYTEA".to_string(),
+            DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+            DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+            DataType::Timestamp(_, None) => "TIMESTAMP".to_string(),
+            DataType::Timestamp(_, Some(_)) => "TIMESTAMP WITH TIME ZONE".to_string(),
+            DataType::Dictionary(_, value_type) => self.column_type(value_type, max_width),
+            _ => "TEXT".to_string(),
+        }
+    }
+
+    /// Writes a CREATE TABLE statement; columns are (quoted name, type, nullable)
+    fn write_create_table(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[(String, String, bool)],
+    ) -> AppResult<()> {
+        writeln!(out, "CREATE TABLE {} (", table)?;
+        for (index, (name, column_type, nullable)) in columns.iter().enumerate() {
+            let separator = if index + 1 < columns.len() { "," } else { "" };
+            let null = if *nullable { "" } else { " NOT NULL" };
+            writeln!(out, "  {} {}{}{}", name, column_type, null, separator)?;
+        }
+        out.write_all(b");")?;
+        Ok(())
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+
+    /// Quotes a table name, qualified names such as `db.table` part by part
+    fn quote_table_name(&self, table_name: &str) -> String {
+        table_name
+            .split('.')
+            .map(|part| self.quote_identifier(part))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    fn quote_string(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "TRUE" } else { "FALSE" }.to_string()
+    }
+
+    /// Renders NaN, Infinity and -Infinity; databases without such values get NULL
+    fn non_finite_literal(&self, _value: &str) -> String {
+        "NULL".to_string()
+    }
+
+    fn date_literal(&self, value: &str) -> String {
+        format!("DATE {}", self.quote_string(value))
+    }
+
+    fn time_literal(&self, value: &str) -> String {
+        format!("TIME {}", self.quote_string(value))
+    }
+
+    fn timestamp_literal(&self, value: &str) -> String {
+        format!("TIMESTAMP {}", self.quote_string(value))
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("X'{}'", to_hex(bytes))
+    }
+
+    /// Whether the script can be wrapped in a transaction, checked before any output is written
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    /// Statement opening a transaction, or None where one is started implicitly
+    fn begin_transaction(&self) -> Option<&'static str> {
+        Some("BEGIN;")
+    }
+
+    fn commit_transaction(&self) -> &'static str {
+        "COMMIT;"
+    }
+
+    /// Upper bound on the number of rows in one INSERT, UPSERT or DELETE statement
+    fn max_rows_per_insert(&self) -> Option<usize> {
+        None
+    }
+
+    /// Upper bound on the size in bytes of one INSERT statement
+    fn max_statement_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Writes a single INSERT statement for `rows`, with table and columns already quoted
+    fn write_insert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        writeln!(out, "INSERT INTO {} ({}) VALUES", table, columns.join(", "))?;
+        write_values(out, rows)?;
+        out.write_all(b";")?;
+        Ok(())
+    }
+
+    /// Whether `write_upsert` is implemented, checked before any output is written
+    fn supports_upsert(&self) -> bool {
+        false
+    }
+
+    /// Writes a statement inserting `rows` and updating the existing rows that match
+    /// the key columns, given as positions in `columns`
+    fn write_upsert(
+        &self,
+        _out: &mut dyn Write,
+        _table: &str,
+        _columns: &[String],
+        _keys: &[usize],
+        _rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        Err(AppError::BadRequest {
+            message: format!("UPSERT scripts are not supported for {}", self.name()),
+        })
+    }
+
+    /// Writes a single DELETE statement matching every key tuple in `rows`
+    fn write_delete(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        keys: &[String],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        let values = if keys.len() == 1 {
+            rows.iter().map(|row| row[0].clone()).collect::<Vec<_>>()
+        } else {
+            rows.iter()
+                .map(|row| format!("({})", row.join(", ")))
+                .collect::<Vec<_>>()
+        };
+        let target = match keys {
+            [key] => key.clone(),
+            _ => format!("({})", keys.join(", ")),
+        };
+        write!(
+            out,
+            "DELETE FROM {} WHERE {} IN ({});",
+            table,
+            target,
+            values.join(", ")
+        )?;
+        Ok(())
+    }
+
+    fn write_update(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        set_clause: &str,
+        where_clause: &str,
+    ) -> AppResult<()> {
+        write!(
+            out,
+            "UPDATE {} SET {} WHERE {};",
+            table, set_clause, where_clause
+        )?;
+        Ok(())
+    }
+}
+
+pub struct MySqlDialect;
+
+impl SqlDialect for MySqlDialect {
+    fn name(&self) -> &'static str {
+        "MySQL"
+    }
+
+    /// Naive timestamps become DATETIME, since TIMESTAMP is converted to UTC and
+    /// limited to 1970-2038
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean => "TINYINT(1)".to_string(),
+            DataType::Int8 => "TINYINT".to_string(),
+            DataType::Int16 => "SMALLINT".to_string(),
+            DataType::Int32 => "INT".to_string(),
+            DataType::Int64 => "BIGINT".to_string(),
+            DataType::UInt8 => "TINYINT UNSIGNED".to_string(),
+            DataType::UInt16 => "SMALLINT UNSIGNED".to_string(),
+            DataType::UInt32 => "INT UNSIGNED".to_string(),
+            DataType::UInt64 => "BIGINT UNSIGNED".to_string(),
+            DataType::Float16 | DataType::Float32 => "FLOAT".to_string(),
+            DataType::Float64 => "DOUBLE".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                decimal_type("DECIMAL", *precision, *scale, 65)
+            }
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => match max_width {
+                // Longer VARCHARs no longer fit the 65535 byte row limit with utf8mb4
+                Some(width) if width <= 16_383 => format!("VARCHAR({})", width.max(1)),
+                Some(_) => "LONGTEXT".to_string(),
+                None => "TEXT".to_string(),
+            },
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => "LONGBLOB".to_string(),
+            DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+            DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+            DataType::Timestamp(unit, None) => {
+                format!("DATETIME({})", fractional_digits(unit).min(6))
+            }
+            DataType::Timestamp(unit, Some(_)) => {
+                format!("TIMESTAMP({})", fractional_digits(unit).min(6))
+            }
+            DataType::Dictionary(_, value_type) => self.column_type(value_type, max_width),
+            _ => "TEXT".to_string(),
+        }
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("`{}`", identifier.replace('`', "``"))
+    }
+
+    /// MySQL treats backslashes as escape characters by default
+    fn quote_string(&self, value: &str) -> String {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('\'', "''")
+            .replace('\0', "\\0");
+        format!("'{}'", escaped)
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "1" } else { "0" }.to_string()
+    }
+
+    fn begin_transaction(&self) -> Option<&'static str> {
+        Some("START TRANSACTION;")
+    }
+
+    /// Stays below the 4MB max_allowed_packet default of older servers
+    fn max_statement_size(&self) -> Option<usize> {
+        Some(4 * 1024 * 1024)
+    }
+
+    fn supports_upsert(&self) -> bool {
+        true
+    }
+
+    fn write_upsert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        keys: &[usize],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        let mut updates = update_columns(columns, keys)
+            .map(|c| format!("{} = VALUES({})", c, c))
+            .collect::<Vec<_>>();
+        // A no-op assignment keeps duplicates from failing when every column is a key
+        if updates.is_empty() {
+            updates = keys
+                .iter()
+                .map(|&k| format!("{} = {}", columns[k], columns[k]))
+                .collect();
+        }
+
+        writeln!(out, "INSERT INTO {} ({}) VALUES", table, columns.join(", "))?;
+        write_values(out, rows)?;
+        write!(out, "\nON DUPLICATE KEY UPDATE {};", updates.join(", "))?;
+        Ok(())
+    }
+}
+
+pub struct PostgreSqlDialect;
+
+impl SqlDialect for PostgreSqlDialect {
+    fn name(&self) -> &'static str {
+        "PostgreSQL"
+    }
+
+    fn non_finite_literal(&self, value: &str) -> String {
+        format!("'{}'", value)
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("'\\x{}'::bytea", to_hex(bytes))
+    }
+
+    fn supports_upsert(&self) -> bool {
+        true
+    }
+
+    fn write_upsert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        keys: &[usize],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        write_on_conflict(out, table, columns, keys, rows)
+    }
+}
+
+pub struct SqliteDialect;
+
+impl SqlDialect for SqliteDialect {
+    fn name(&self) -> &'static str {
+        "SQLite"
+    }
+
+    /// SQLite only has storage classes, so types map onto their affinities
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => "INTEGER".to_string(),
+            DataType::Float16 | DataType::Float32 | DataType::Float64 => "REAL".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                format!("NUMERIC({}, {})", precision, scale)
+            }
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => "BLOB".to_string(),
+            DataType::Timestamp(_, _) => "DATETIME".to_string(),
+            DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+            DataType::Dictionary(_, value_type) => self.column_type(value_type, max_width),
+            _ => "TEXT".to_string(),
+        }
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "1" } else { "0" }.to_string()
+    }
+
+    fn date_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn time_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn timestamp_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    /// Multi-row VALUES is a compound SELECT, capped by SQLITE_MAX_COMPOUND_SELECT
+    fn max_rows_per_insert(&self) -> Option<usize> {
+        Some(500)
+    }
+
+    /// SQLITE_MAX_SQL_LENGTH default
+    fn max_statement_size(&self) -> Option<usize> {
+        Some(1_000_000)
+    }
+
+    fn supports_upsert(&self) -> bool {
+        true
+    }
+
+    fn write_upsert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        keys: &[usize],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        write_on_conflict(out, table, columns, keys, rows)
+    }
+
+    /// Composite keys are matched with row values, which SQLite supports since 3.15
+    fn write_delete(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        keys: &[String],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        if keys.len() == 1 {
+            let values = rows.iter().map(|row| row[0].as_str()).collect::<Vec<_>>();
+            write!(
+                out,
+                "DELETE FROM {} WHERE {} IN ({});",
+                table,
+                keys[0],
+                values.join(", ")
+            )?;
+        } else {
+            let values = rows
+                .iter()
+                .map(|row| format!("({})", row.join(", ")))
+                .collect::<Vec<_>>();
+            write!(
+                out,
+                "DELETE FROM {} WHERE ({}) IN (VALUES {});",
+                table,
+                keys.join(", "),
+                values.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct MsSqlDialect;
+
+impl SqlDialect for MsSqlDialect {
+    fn name(&self) -> &'static str {
+        "SQLServer"
+    }
+
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean => "BIT".to_string(),
+            DataType::UInt8 => "TINYINT".to_string(),
+            DataType::Int8 | DataType::Int16 => "SMALLINT".to_string(),
+            DataType::Int32 | DataType::UInt16 => "INT".to_string(),
+            DataType::Int64 | DataType::UInt32 => "BIGINT".to_string(),
+            DataType::UInt64 => "DECIMAL(20, 0)".to_string(),
+            DataType::Float16 | DataType::Float32 => "REAL".to_string(),
+            DataType::Float64 => "FLOAT".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                decimal_type("DECIMAL", *precision, *scale, 38)
+            }
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => match max_width {
+                Some(width) if width <= 4000 => format!("NVARCHAR({})", width.max(1)),
+                _ => "NVARCHAR(MAX)".to_string(),
+            },
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => "VARBINARY(MAX)".to_string(),
+            DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+            DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+            DataType::Timestamp(unit, None) => {
+                format!("DATETIME2({})", fractional_digits(unit).min(7))
+            }
+            DataType::Timestamp(unit, Some(_)) => {
+                format!("DATETIMEOFFSET({})", fractional_digits(unit).min(7))
+            }
+            DataType::Dictionary(_, value_type) => self.column_type(value_type, max_width),
+            _ => "NVARCHAR(MAX)".to_string(),
+        }
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("[{}]", identifier.replace(']', "]]"))
+    }
+
+    fn quote_string(&self, value: &str) -> String {
+        format!("N'{}'", value.replace('\'', "''"))
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "1" } else { "0" }.to_string()
+    }
+
+    fn date_literal(&self, value: &str) -> String {
+        format!("CAST({} AS DATE)", self.quote_string(value))
+    }
+
+    fn time_literal(&self, value: &str) -> String {
+        format!("CAST({} AS TIME)", self.quote_string(value))
+    }
+
+    fn timestamp_literal(&self, value: &str) -> String {
+        format!("CAST({} AS DATETIME2)", self.quote_string(value))
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("0x{}", to_hex(bytes))
+    }
+
+    fn begin_transaction(&self) -> Option<&'static str> {
+        Some("BEGIN TRANSACTION;")
+    }
+
+    /// A table value constructor accepts at most 1000 rows
+    fn max_rows_per_insert(&self) -> Option<usize> {
+        Some(1000)
+    }
+
+    fn supports_upsert(&self) -> bool {
+        true
+    }
+
+    fn write_upsert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        keys: &[usize],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        writeln!(out, "MERGE INTO {} AS target", table)?;
+        writeln!(out, "USING (VALUES")?;
+        write_values(out, rows)?;
+        writeln!(out, "\n) AS source ({})", columns.join(", "))?;
+        writeln!(out, "ON {}", merge_condition(columns, keys))?;
+        write_merge_actions(out, columns, keys)
+    }
+
+    /// SQL Server has no row value comparison, so composite keys become OR-ed conditions
+    fn write_delete(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        keys: &[String],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        if keys.len() == 1 {
+            let values = rows.iter().map(|row| row[0].as_str()).collect::<Vec<_>>();
+            write!(
+                out,
+                "DELETE FROM {} WHERE {} IN ({});",
+                table,
+                keys[0],
+                values.join(", ")
+            )?;
+        } else {
+            let conditions = rows
+                .iter()
+                .map(|row| {
+                    let matches = keys
+                        .iter()
+                        .zip(row)
+                        .map(|(key, value)| format!("{} = {}", key, value))
+                        .collect::<Vec<_>>();
+                    format!("({})", matches.join(" AND "))
+                })
+                .collect::<Vec<_>>();
+            write!(
+                out,
+                "DELETE FROM {} WHERE {};",
+                table,
+                conditions.join("\n   OR ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct OracleDialect;
+
+impl SqlDialect for OracleDialect {
+    fn name(&self) -> &'static str {
+        "Oracle"
+    }
+
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean => "NUMBER(1)".to_string(),
+            DataType::Int8 | DataType::UInt8 => "NUMBER(3)".to_string(),
+            DataType::Int16 | DataType::UInt16 => "NUMBER(5)".to_string(),
+            DataType::Int32 | DataType::UInt32 => "NUMBER(10)".to_string(),
+            DataType::Int64 | DataType::UInt64 => "NUMBER(20)".to_string(),
+            DataType::Float16 | DataType::Float32 => "BINARY_FLOAT".to_string(),
+            DataType::Float64 => "BINARY_DOUBLE".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                decimal_type("NUMBER", *precision, *scale, 38)
+            }
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => match max_width {
+                Some(width) if width <= 4000 => format!("VARCHAR2({} CHAR)", width.max(1)),
+                _ => "CLOB".to_string(),
+            },
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => "BLOB".to_string(),
+            DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+            DataType::Time32(_) | DataType::Time64(_) => "VARCHAR2(18)".to_string(),
+            DataType::Timestamp(unit, None) => {
+                format!("TIMESTAMP({})", fractional_digits(unit))
+            }
+            DataType::Timestamp(unit, Some(_)) => {
+                format!("TIMESTAMP({}) WITH TIME ZONE", fractional_digits(unit))
+            }
+            DataType::Dictionary(_, value_type) => self.column_type(value_type, max_width),
+            _ => "CLOB".to_string(),
+        }
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "1" } else { "0" }.to_string()
+    }
+
+    fn non_finite_literal(&self, value: &str) -> String {
+        match value {
+            "NaN" => "BINARY_DOUBLE_NAN",
+            "-Infinity" => "-BINARY_DOUBLE_INFINITY",
+            _ => "BINARY_DOUBLE_INFINITY",
+        }
+        .to_string()
+    }
+
+    fn time_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("HEXTORAW('{}')", to_hex(bytes))
+    }
+
+    /// Oracle starts a transaction with the first DML statement
+    fn begin_transaction(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// An `IN` list holds at most 1000 expressions (ORA-01795)
+    fn max_rows_per_insert(&self) -> Option<usize> {
+        Some(1000)
+    }
+
+    /// Oracle has no multi-row VALUES, so rows are batched with INSERT ALL
+    fn write_insert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        writeln!(out, "INSERT ALL")?;
+        for row in rows {
+            writeln!(
+                out,
+                "  INTO {} ({}) VALUES ({})",
+                table,
+                columns.join(", "),
+                row.join(", ")
+            )?;
+        }
+        out.write_all(b"SELECT 1 FROM DUAL;")?;
+        Ok(())
+    }
+
+    fn supports_upsert(&self) -> bool {
+        true
+    }
+
+    fn write_upsert(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[String],
+        keys: &[usize],
+        rows: &[Vec<String>],
+    ) -> AppResult<()> {
+        writeln!(out, "MERGE INTO {} target", table)?;
+        writeln!(out, "USING (")?;
+        for (index, row) in rows.iter().enumerate() {
+            if index > 0 {
+                out.write_all(b" UNION ALL\n")?;
+            }
+            let values = row
+                .iter()
+                .zip(columns)
+                .map(|(value, column)| format!("{} AS {}", value, column))
+                .collect::<Vec<_>>();
+            write!(out, "  SELECT {} FROM DUAL", values.join(", "))?;
+        }
+        writeln!(out, "\n) source")?;
+        writeln!(out, "ON ({})", merge_condition(columns, keys))?;
+        write_merge_actions(out, columns, keys)
+    }
+}
+
+pub struct ClickHouseDialect;
+
+impl SqlDialect for ClickHouseDialect {
+    fn name(&self) -> &'static str {
+        "ClickHouse"
+    }
+
+    fn column_type(&self, data_type: &DataType, max_width: Option<usize>) -> String {
+        match data_type {
+            DataType::Boolean => "Bool".to_string(),
+            DataType::Int8 => "Int8".to_string(),
+            DataType::Int16 => "Int16".to_string(),
+            DataType::Int32 => "Int32".to_string(),
+            DataType::Int64 => "Int64".to_string(),
+            DataType::UInt8 => "UInt8".to_string(),
+            DataType::UInt16 => "UInt16".to_string(),
+            DataType::UInt32 => "UInt32".to_string(),
+            DataType::UInt64 => "UInt64".to_string(),
+            DataType::Float16 | DataType::Float32 => "Float32".to_string(),
+            DataType::Float64 => "Float64".to_string(),
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                decimal_type("Decimal", *precision, *scale, 76)
+            }
+            DataType::Date32 | DataType::Date64 => "Date32".to_string(),
+            DataType::Timestamp(unit, None) => {
+                format!("DateTime64({})", fractional_digits(unit))
+            }
+            DataType::Timestamp(unit, Some(tz)) => {
+                format!("DateTime64({}, '{}')", fractional_digits(unit), tz)
+            }
+            DataType::Dictionary(_, value_type) => {
+                format!(
+                    "LowCardinality({})",
+                    self.column_type(value_type, max_width)
+                )
+            }
+            _ => "String".to_string(),
+        }
+    }
+
+    /// ClickHouse has no multi-statement transactions for MergeTree tables
+    fn supports_transactions(&self) -> bool {
+        false
+    }
+
+    /// Nullability is part of the ClickHouse type, and every table needs an engine
+    fn write_create_table(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        columns: &[(String, String, bool)],
+    ) -> AppResult<()> {
+        writeln!(out, "CREATE TABLE {} (", table)?;
+        for (index, (name, column_type, nullable)) in columns.iter().enumerate() {
+            let separator = if index + 1 < columns.len() { "," } else { "" };
+            if *nullable {
+                writeln!(out, "  {} Nullable({}){}", name, column_type, separator)?;
+            } else {
+                writeln!(out, "  {} {}{}", name, column_type, separator)?;
+            }
+        }
+        out.write_all(b") ENGINE = MergeTree ORDER BY tuple();")?;
+        Ok(())
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("`{}`", identifier.replace('`', "``"))
+    }
+
+    fn quote_string(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+
+    fn boolean_literal(&self, value: bool) -> String {
+        if value { "true" } else { "false" }.to_string()
+    }
+
+    fn non_finite_literal(&self, value: &str) -> String {
+        match value {
+            "NaN" => "nan",
+            "-Infinity" => "-inf",
+            _ => "inf",
+        }
+        .to_string()
+    }
+
+    fn date_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn time_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn timestamp_literal(&self, value: &str) -> String {
+        self.quote_string(value)
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("unhex('{}')", to_hex(bytes))
+    }
+
+    /// ClickHouse updates rows through a mutation
+    fn write_update(
+        &self,
+        out: &mut dyn Write,
+        table: &str,
+        set_clause: &str,
+        where_clause: &str,
+    ) -> AppResult<()> {
+        write!(
+            out,
+            "ALTER TABLE {} UPDATE {} WHERE {};",
+            table, set_clause, where_clause
+        )?;
+        Ok(())
+    }
+}
+
+/// Writes `(..), (..)` rows of a multi-row VALUES list
+fn write_values(out: &mut dyn Write, rows: &[Vec<String>]) -> AppResult<()> {
+    for (index, row) in rows.iter().enumerate() {
+        if index > 0 {
+            out.write_all(b",\n")?;
+        }
+        write!(out, "({})", row.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Columns that are not part of the key, and so are overwritten by an UPSERT
+fn update_columns<'a>(
+    columns: &'a [String],
+    keys: &'a [usize],
+) -> impl Iterator<Item = &'a String> {
+    columns
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !keys.contains(index))
+        .map(|(_, column)| column)
+}
+
+/// `INSERT ... ON CONFLICT (...) DO UPDATE`, shared by PostgreSQL and SQLite
+fn write_on_conflict(
+    out: &mut dyn Write,
+    table: &str,
+    columns: &[String],
+    keys: &[usize],
+    rows: &[Vec<String>],
+) -> AppResult<()> {
+    let conflict = keys
+        .iter()
+        .map(|&k| columns[k].as_str())
+        .collect::<Vec<_>>();
+    let updates = update_columns(columns, keys)
+        .map(|c| format!("{} = EXCLUDED.{}", c, c))
+        .collect::<Vec<_>>();
+
+    writeln!(out, "INSERT INTO {} ({}) VALUES", table, columns.join(", "))?;
+    write_values(out, rows)?;
+    if updates.is_empty() {
+        write!(out, "\nON CONFLICT ({}) DO NOTHING;", conflict.join(", "))?;
+    } else {
+        write!(
+            out,
+            "\nON CONFLICT ({}) DO UPDATE SET {};",
+            conflict.join(", "),
+            updates.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+fn merge_condition(columns: &[String], keys: &[usize]) -> String {
+    keys.iter()
+        .map(|&k| format!("target.{} = source.{}", columns[k], columns[k]))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// The WHEN MATCHED / WHEN NOT MATCHED branches of a MERGE statement
+fn write_merge_actions(out: &mut dyn Write, columns: &[String], keys: &[usize]) -> AppResult<()> {
+    let updates = update_columns(columns, keys)
+        .map(|c| format!("target.{} = source.{}", c, c))
+        .collect::<Vec<_>>();
+    if !updates.is_empty() {
+        writeln!(out, "WHEN MATCHED THEN UPDATE SET {}", updates.join(", "))?;
+    }
+
+    let sources = columns
+        .iter()
+        .map(|c| format!("source.{}", c))
+        .collect::<Vec<_>>();
+    write!(
+        out,
+        "WHEN NOT MATCHED THEN INSERT ({}) VALUES ({});",
+        columns.join(", "),
+        sources.join(", ")
+    )?;
+    Ok(())
+}
+
+pub fn dialect_from_str(s: &str) -> AppResult<Box<dyn SqlDialect>> {
+    match s.to_lowercase().as_str() {
+        "mysql" => Ok(Box::new(MySqlDialect)),
+        "postgresql" | "postgres" => Ok(Box::new(PostgreSqlDialect)),
+        "sqlite" => Ok(Box::new(SqliteDialect)),
+        "sqlserver" | "mssql" => Ok(Box::new(MsSqlDialect)),
+        "oracle" => Ok(Box::new(OracleDialect)),
+        "clickhouse" => Ok(Box::new(ClickHouseDialect)),
+        _ => Err(AppError::BadRequest {
+            message: format!(
+                "Invalid dialect: '{}'. Please use 'MySQL', 'PostgreSQL', 'SQLite', 'SQLServer', 'Oracle' or 'ClickHouse'.",
+                s
+            ),
+        }),
+    }
+}
+
+/// Clamps the precision to the dialect's maximum, keeping the scale within it
+fn decimal_type(name: &str, precision: u8, scale: i8, max_precision: u8) -> String {
+    let precision = precision.min(max_precision);
+    let scale = scale.clamp(0, precision as i8);
+    format!("{}({}, {})", name, precision, scale)
+}
+
+fn fractional_digits(unit: &TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{:02X}", byte);
+    }
+    hex
+}
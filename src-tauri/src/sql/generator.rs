@@ -1,236 +1,428 @@
-use crate::commands::query::Dialect;
+use crate::context::error::AppError;
 use crate::context::schema::AppResult;
-use datafusion::arrow::error::ArrowError;
-use datafusion::arrow::record_batch::RecordBatch;
-use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+use crate::sql::dialect::SqlDialect;
+use crate::sql::literal::extract_literals;
 use datafusion::dataframe::DataFrame;
-use datafusion_table_providers::mysql::MySQL;
+use futures::StreamExt;
+use std::io::Write;
 
-/// Helper function to format a value from Arrow Array for SQL
-fn format_value_for_sql(value: &str) -> String {
-    if value == "NULL" {
-        "NULL".to_string()
-    } else {
-        // Escape single quotes and wrap in quotes for string values
-        format!("'{}'", value.replace("'", "''"))
+/// Helper function to extract column headers from the DataFrame schema
+fn extract_headers(df: &DataFrame) -> Vec<String> {
+    df.schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().to_string())
+        .collect()
+}
+
+/// Groups literal rows into chunks of at most `max_rows` rows, keeping each chunk
+/// under the dialect's row and statement size limits
+pub(crate) struct RowChunker {
+    chunk_limit: usize,
+    size_limit: usize,
+    rows: Vec<Vec<String>>,
+    size: usize,
+}
+
+impl RowChunker {
+    pub(crate) fn new(max_rows: usize, db_dialect: &dyn SqlDialect) -> Self {
+        let chunk_limit = match db_dialect.max_rows_per_insert() {
+            Some(limit) => max_rows.min(limit),
+            None => max_rows,
+        }
+        .max(1);
+
+        Self {
+            chunk_limit,
+            size_limit: db_dialect.max_statement_size().unwrap_or(usize::MAX),
+            rows: Vec::with_capacity(chunk_limit),
+            size: 0,
+        }
+    }
+
+    /// Adds a row, handing every chunk that fills up to `on_chunk`
+    pub(crate) fn push<F>(&mut self, row: Vec<String>, on_chunk: &mut F) -> AppResult<()>
+    where
+        F: FnMut(&[Vec<String>]) -> AppResult<()>,
+    {
+        // Row literals plus the ", " separators, the parentheses and ",\n"
+        let row_size = row.iter().map(|v| v.len() + 2).sum::<usize>() + 2;
+        if !self.rows.is_empty() && self.size + row_size > self.size_limit {
+            self.finish(on_chunk)?;
+        }
+
+        self.rows.push(row);
+        self.size += row_size;
+        if self.rows.len() == self.chunk_limit {
+            self.finish(on_chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Hands the pending rows, if any, to `on_chunk`
+    pub(crate) fn finish<F>(&mut self, on_chunk: &mut F) -> AppResult<()>
+    where
+        F: FnMut(&[Vec<String>]) -> AppResult<()>,
+    {
+        if !self.rows.is_empty() {
+            on_chunk(&self.rows)?;
+            self.rows.clear();
+            self.size = 0;
+        }
+        Ok(())
     }
 }
 
-/// Helper function to extract rows from RecordBatch in a streaming fashion
-fn extract_rows_from_batches<F>(batches: Vec<RecordBatch>, mut on_row: F) -> AppResult<()>
+/// Streams the DataFrame as SQL literals in chunks of at most `max_rows` rows, keeping
+/// each chunk under the dialect's statement size, and hands every chunk to `write_chunk`.
+/// Statements are separated by a newline; returns the number of rows processed.
+async fn write_chunks<W, P, F>(
+    df: DataFrame,
+    max_rows: usize,
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+    mut on_progress: P,
+    mut write_chunk: F,
+) -> AppResult<usize>
 where
-    F: FnMut(Vec<String>) -> AppResult<()>,
+    W: Write,
+    P: FnMut(usize),
+    F: FnMut(&mut W, &[Vec<String>]) -> AppResult<()>,
 {
-    let Some(first_batch) = batches.first() else {
-        return Ok(());
-    };
+    let mut chunker = RowChunker::new(max_rows, db_dialect);
+    let mut is_first_chunk = true;
+    let mut rows_written = 0;
 
-    let width = first_batch.schema().fields().len();
-    let options = FormatOptions::default().with_null("NULL");
-
-    for batch in batches {
-        let formatters = batch
-            .columns()
-            .iter()
-            .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
-            .collect::<Result<Vec<_>, ArrowError>>()
-            .map_err(|e| crate::context::error::AppError::BadRequest {
-                message: format!("Failed to create formatter: {}", e),
-            })?;
-
-        for row_idx in 0..batch.num_rows() {
-            let mut cells = Vec::with_capacity(width);
-            for formatter in &formatters {
-                let formatted_value = formatter.value(row_idx).to_string();
-                // Check if the value is numeric or boolean (not wrapped in quotes)
-                let sql_value = if formatted_value == "NULL" {
-                    "NULL".to_string()
-                } else if formatted_value.parse::<i64>().is_ok()
-                    || formatted_value.parse::<f64>().is_ok()
-                    || formatted_value == "true"
-                    || formatted_value == "false"
-                {
-                    formatted_value
-                } else {
-                    format_value_for_sql(&formatted_value)
-                };
-                cells.push(sql_value);
-            }
-            on_row(cells)?;
+    let mut flush_chunk = |rows: &[Vec<String>]| -> AppResult<()> {
+        if !is_first_chunk {
+            out.write_all(b"\n")?;
+        } else {
+            is_first_chunk = false;
         }
+        write_chunk(out, rows)
+    };
+
+    let mut stream = df.execute_stream().await?;
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        extract_literals(&batch, db_dialect, |row| {
+            chunker.push(row, &mut flush_chunk)
+        })?;
+        rows_written += batch.num_rows();
+        on_progress(rows_written);
     }
+    chunker.finish(&mut flush_chunk)?;
 
-    Ok(())
+    Ok(rows_written)
 }
 
-/// Helper function to extract column headers from the provided RecordBatches
-fn extract_headers_from_batches(batches: &[RecordBatch]) -> Vec<String> {
-    batches
-        .first()
-        .map(|batch| {
-            batch
-                .schema()
-                .fields()
+/// Matches the key columns at `keys` against the values of `row`. `= NULL` never
+/// matches, so NULL keys are compared with IS NULL.
+fn key_condition(quoted_headers: &[String], keys: &[usize], row: &[String]) -> String {
+    keys.iter()
+        .map(|&index| match row[index].as_str() {
+            "NULL" => format!("{} IS NULL", quoted_headers[index]),
+            value => format!("{} = {}", quoted_headers[index], value),
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Resolves key column names to their positions in the DataFrame headers
+fn key_indexes(headers: &[String], key_columns: &[String]) -> AppResult<Vec<usize>> {
+    if key_columns.is_empty() {
+        return Err(AppError::BadRequest {
+            message: "At least one key column is required".to_string(),
+        });
+    }
+
+    key_columns
+        .iter()
+        .map(|key| {
+            headers
                 .iter()
-                .map(|f| f.name().to_string())
-                .collect()
+                .position(|h| h == key)
+                .ok_or_else(|| AppError::BadRequest {
+                    message: format!("WHERE column '{}' not found in data", key),
+                })
         })
-        .unwrap_or_default()
+        .collect()
 }
 
 /// Generate SQL insert statements from DataFrame
-/// Splits large datasets into multiple INSERT statements for better performance
-pub async fn generate_sql_inserts(
+/// Splits large datasets into multiple INSERT statements for better performance,
+/// respecting the dialect's row and statement size limits.
+/// Batches are streamed into `out` as they are produced, and `on_progress` receives
+/// the number of rows written so far after each batch.
+pub async fn generate_sql_inserts<W, P>(
     df: DataFrame,
     table_name: &str,
     max_values_per_insert: usize,
-    db_dialect: &Dialect,
-) -> AppResult<String> {
-    // Collect RecordBatches from DataFrame
-    let batches = df
-        .collect()
-        .await
-        .map_err(|e| crate::context::error::AppError::BadRequest {
-            message: format!("Failed to collect DataFrame: {}", e),
-        })?;
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+    on_progress: P,
+) -> AppResult<usize>
+where
+    W: Write,
+    P: FnMut(usize),
+{
+    let headers = extract_headers(&df);
+    if headers.is_empty() {
+        return Ok(0);
+    }
 
-    let headers = extract_headers_from_batches(&batches);
+    let table = db_dialect.quote_table_name(table_name);
+    let columns = headers
+        .iter()
+        .map(|h| db_dialect.quote_identifier(h))
+        .collect::<Vec<String>>();
+
+    write_chunks(
+        df,
+        max_values_per_insert,
+        db_dialect,
+        out,
+        on_progress,
+        |out, rows| db_dialect.write_insert(out, &table, &columns, rows),
+    )
+    .await
+}
+
+/// Generate UPSERT statements from DataFrame
+/// Uses the dialect's native form: ON DUPLICATE KEY UPDATE for MySQL,
+/// ON CONFLICT for PostgreSQL and SQLite, and MERGE for SQL Server and Oracle.
+pub async fn generate_sql_upserts<W, P>(
+    df: DataFrame,
+    table_name: &str,
+    key_columns: &[String],
+    max_values_per_insert: usize,
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+    on_progress: P,
+) -> AppResult<usize>
+where
+    W: Write,
+    P: FnMut(usize),
+{
+    let headers = extract_headers(&df);
     if headers.is_empty() {
-        return Ok(String::new());
+        return Ok(0);
     }
+    let keys = key_indexes(&headers, key_columns)?;
 
-    let mut sql_statements = String::new();
+    let table = db_dialect.quote_table_name(table_name);
     let columns = headers
         .iter()
-        .map(|h| match db_dialect {
-            Dialect::MySQL => format!("`{}`", h),
-            Dialect::PostgreSQL => format!("\"{}\"", h),
-        })
-        .collect::<Vec<String>>()
-        .join(", ");
-    let insert_header_template = match db_dialect {
-        Dialect::MySQL => format!("INSERT INTO `{}` ({}) VALUES\n", table_name, columns),
-        Dialect::PostgreSQL => format!("INSERT INTO \"{}\" ({}) VALUES\n", table_name, columns)
-    };
-    let chunk_limit = max_values_per_insert.max(1);
-    let mut pending_rows: Vec<Vec<String>> = Vec::with_capacity(chunk_limit);
-    let mut is_first_insert = true;
+        .map(|h| db_dialect.quote_identifier(h))
+        .collect::<Vec<String>>();
 
-    let mut flush_chunk = |rows: &mut Vec<Vec<String>>| -> AppResult<()> {
-        if rows.is_empty() {
-            return Ok(());
-        }
+    write_chunks(
+        df,
+        max_values_per_insert,
+        db_dialect,
+        out,
+        on_progress,
+        |out, rows| db_dialect.write_upsert(out, &table, &columns, &keys, rows),
+    )
+    .await
+}
 
-        if !is_first_insert {
-            sql_statements.push('\n');
-        } else {
-            is_first_insert = false;
+/// Generate DELETE statements from DataFrame
+/// Deletes the rows whose key columns match, batching keys into `WHERE key IN (...)`.
+pub async fn generate_sql_deletes<W, P>(
+    df: DataFrame,
+    table_name: &str,
+    key_columns: &[String],
+    max_values_per_delete: usize,
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+    on_progress: P,
+) -> AppResult<usize>
+where
+    W: Write,
+    P: FnMut(usize),
+{
+    let headers = extract_headers(&df);
+    if headers.is_empty() {
+        return Ok(0);
+    }
+    key_indexes(&headers, key_columns)?;
+
+    let table = db_dialect.quote_table_name(table_name);
+    let keys = key_columns
+        .iter()
+        .map(|k| db_dialect.quote_identifier(k))
+        .collect::<Vec<String>>();
+
+    // Only the key columns need to be rendered
+    let df = df.select_columns(&key_columns.iter().map(|k| k.as_str()).collect::<Vec<_>>())?;
+
+    write_chunks(
+        df,
+        max_values_per_delete,
+        db_dialect,
+        out,
+        on_progress,
+        |out, rows| {
+            // `IN` never matches NULL, so rows with a NULL key get their own statement
+            let (null_rows, rows): (Vec<_>, Vec<_>) = rows
+                .iter()
+                .cloned()
+                .partition(|row| row.iter().any(|value| value == "NULL"));
+
+            let mut is_first_statement = true;
+            if !rows.is_empty() {
+                db_dialect.write_delete(out, &table, &keys, &rows)?;
+                is_first_statement = false;
+            }
+            for row in null_rows {
+                if !is_first_statement {
+                    out.write_all(b"\n")?;
+                } else {
+                    is_first_statement = false;
+                }
+                let indexes = (0..keys.len()).collect::<Vec<_>>();
+                write!(
+                    out,
+                    "DELETE FROM {} WHERE {};",
+                    table,
+                    key_condition(&keys, &indexes, &row)
+                )?;
+            }
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// Options for generated UPDATE statements
+pub struct UpdateOptions {
+    key_columns: Vec<String>,
+    set_columns: Option<Vec<String>>,
+    exclude_columns: Vec<String>,
+    skip_nulls: bool,
+}
+
+impl UpdateOptions {
+    pub fn new(key_columns: Vec<String>) -> Self {
+        Self {
+            key_columns,
+            set_columns: None,
+            exclude_columns: Vec::new(),
+            skip_nulls: false,
         }
+    }
 
-        sql_statements.push_str(&insert_header_template);
+    /// Only these columns are assigned in the SET clause
+    pub fn with_set_columns(mut self, set_columns: Vec<String>) -> Self {
+        self.set_columns = Some(set_columns);
+        self
+    }
 
-        let values_clauses = rows
-            .iter()
-            .map(|row| format!("({})", row.join(", ")))
-            .collect::<Vec<String>>()
-            .join(",\n");
+    /// These columns are left out of the SET clause
+    pub fn with_exclude_columns(mut self, exclude_columns: Vec<String>) -> Self {
+        self.exclude_columns = exclude_columns;
+        self
+    }
 
-        sql_statements.push_str(&values_clauses);
-        sql_statements.push(';');
-        rows.clear();
-        Ok(())
-    };
+    /// NULL values are left untouched instead of overwriting the target column
+    pub fn with_skip_nulls(mut self, skip_nulls: bool) -> Self {
+        self.skip_nulls = skip_nulls;
+        self
+    }
 
-    extract_rows_from_batches(batches, |row| {
-        pending_rows.push(row);
-        if pending_rows.len() == chunk_limit {
-            flush_chunk(&mut pending_rows)?;
+    /// Positions of the columns assigned in the SET clause
+    fn set_indexes(&self, headers: &[String], keys: &[usize]) -> AppResult<Vec<usize>> {
+        for column in self
+            .set_columns
+            .iter()
+            .flatten()
+            .chain(&self.exclude_columns)
+        {
+            if !headers.contains(column) {
+                return Err(AppError::BadRequest {
+                    message: format!("Column '{}' not found in data", column),
+                });
+            }
         }
-        Ok(())
-    })?;
-    flush_chunk(&mut pending_rows)?;
 
-    Ok(sql_statements)
+        Ok(headers
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !keys.contains(index))
+            .filter(|(_, header)| match self.set_columns {
+                Some(ref set_columns) => set_columns.contains(header),
+                None => true,
+            })
+            .filter(|(_, header)| !self.exclude_columns.contains(header))
+            .map(|(index, _)| index)
+            .collect())
+    }
 }
 
 /// Generate SQL UPDATE statements from DataFrame
-/// Creates UPDATE statements with WHERE conditions based on the specified column
-pub async fn generate_sql_update(
+/// Creates one UPDATE statement per row, matched on the key columns, and streams
+/// them into `out` batch by batch like `generate_sql_inserts`.
+pub async fn generate_sql_update<W, P>(
     df: DataFrame,
     table_name: &str,
-    where_column: &str,
-    db_dialect: &Dialect,
-) -> AppResult<String> {
-    // Collect RecordBatches from DataFrame
-    let batches = df
-        .collect()
-        .await
-        .map_err(|e| crate::context::error::AppError::BadRequest {
-            message: format!("Failed to collect DataFrame: {}", e),
-        })?;
-
-    let headers = extract_headers_from_batches(&batches);
+    options: &UpdateOptions,
+    db_dialect: &dyn SqlDialect,
+    out: &mut W,
+    mut on_progress: P,
+) -> AppResult<usize>
+where
+    W: Write,
+    P: FnMut(usize),
+{
+    let headers = extract_headers(&df);
 
     if headers.is_empty() {
-        return Ok(String::new());
+        return Ok(0);
     }
 
-    // Validate that the WHERE column exists
-    if !headers.contains(&where_column.to_string()) {
-        return Err(crate::context::error::AppError::BadRequest {
-            message: format!("WHERE column '{}' not found in data", where_column),
+    let keys = key_indexes(&headers, &options.key_columns)?;
+    let set_indexes = options.set_indexes(&headers, &keys)?;
+    if set_indexes.is_empty() {
+        return Err(AppError::BadRequest {
+            message: "No columns left to SET in UPDATE statements".to_string(),
         });
     }
 
-    // Find the index of the WHERE column
-    let where_column_index = headers
+    let table = db_dialect.quote_table_name(table_name);
+    let quoted_headers = headers
         .iter()
-        .position(|h| h == where_column)
-        .ok_or_else(|| crate::context::error::AppError::BadRequest {
-            message: format!("WHERE column '{}' not found", where_column),
-        })?;
+        .map(|h| db_dialect.quote_identifier(h))
+        .collect::<Vec<String>>();
 
-    let mut sql_statements = String::new();
+    let mut is_first_statement = true;
+    let mut rows_written = 0;
+    let mut stream = df.execute_stream().await?;
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        extract_literals(&batch, db_dialect, |row| {
+            let set_clauses = set_indexes
+                .iter()
+                .filter(|&&index| !(options.skip_nulls && row[index] == "NULL"))
+                .map(|&index| format!("{} = {}", quoted_headers[index], row[index]))
+                .collect::<Vec<_>>();
 
-    extract_rows_from_batches(batches, |row| {
-        let mut set_clauses = Vec::new();
+            if !set_clauses.is_empty() {
+                let where_clause = key_condition(&quoted_headers, &keys, &row);
 
-        for (col_index, (header, value)) in headers.iter().zip(row.iter()).enumerate() {
-            if col_index != where_column_index {
-                set_clauses.push(match db_dialect {
-                    Dialect::MySQL => format!("`{}` = {}", header, value),
-                    Dialect::PostgreSQL => format!("\"{}\" = {}", header, value)
-                });
+                if !is_first_statement {
+                    out.write_all(b"\n")?;
+                } else {
+                    is_first_statement = false;
+                }
+                db_dialect.write_update(out, &table, &set_clauses.join(", "), &where_clause)?;
             }
-        }
-
-        if !set_clauses.is_empty() {
-            let where_value = &row[where_column_index];
-            let where_clause = 
-            match db_dialect {
-                Dialect::MySQL => format!("`{}` = {}", where_column, where_value),
-                Dialect::PostgreSQL => format!("\"{}\" = {}", where_column, where_value)
-            };
-            let update_statement = 
-            match db_dialect {
-                Dialect::MySQL => format!(
-                    "UPDATE `{}` SET {} WHERE {};\n",
-                    table_name,
-                    set_clauses.join(", "),
-                    where_clause
-                ),
-                Dialect::PostgreSQL => format!(
-                    "UPDATE \"{}\" SET {} WHERE {};\n",
-                    table_name,
-                    set_clauses.join(", "),
-                    where_clause
-                )
-            };
-            sql_statements.push_str(&update_statement);
-        }
 
-        Ok(())
-    })?;
+            Ok(())
+        })?;
+        rows_written += batch.num_rows();
+        on_progress(rows_written);
+    }
 
-    Ok(sql_statements)
+    Ok(rows_written)
 }
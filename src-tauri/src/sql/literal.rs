@@ -0,0 +1,97 @@
+use crate::context::schema::AppResult;
+use crate::sql::dialect::SqlDialect;
+use datafusion::arrow::array::{Array, AsArray};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+const TIMESTAMP_TZ_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f%:z";
+
+/// Renders every row of a batch as SQL literals, choosing the literal form from
+/// each column's Arrow type rather than from its formatted text
+pub fn extract_literals<F>(
+    batch: &RecordBatch,
+    dialect: &dyn SqlDialect,
+    mut on_row: F,
+) -> AppResult<()>
+where
+    F: FnMut(Vec<String>) -> AppResult<()>,
+{
+    let options = FormatOptions::default()
+        .with_timestamp_format(Some(TIMESTAMP_FORMAT))
+        .with_timestamp_tz_format(Some(TIMESTAMP_TZ_FORMAT))
+        .with_datetime_format(Some(TIMESTAMP_FORMAT));
+
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+        .collect::<Result<Vec<_>, ArrowError>>()?;
+
+    for row in 0..batch.num_rows() {
+        let cells = batch
+            .columns()
+            .iter()
+            .zip(&formatters)
+            .map(|(column, formatter)| render_literal(column.as_ref(), formatter, row, dialect))
+            .collect();
+        on_row(cells)?;
+    }
+
+    Ok(())
+}
+
+fn render_literal(
+    array: &dyn Array,
+    formatter: &ArrayFormatter,
+    row: usize,
+    dialect: &dyn SqlDialect,
+) -> String {
+    if array.is_null(row) {
+        return "NULL".to_string();
+    }
+
+    match array.data_type() {
+        DataType::Boolean => dialect.boolean_literal(array.as_boolean().value(row)),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Decimal128(_, _)
+        | DataType::Decimal256(_, _) => formatter.value(row).to_string(),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            match formatter.value(row).to_string().as_str() {
+                "NaN" => dialect.non_finite_literal("NaN"),
+                "inf" => dialect.non_finite_literal("Infinity"),
+                "-inf" => dialect.non_finite_literal("-Infinity"),
+                value => value.to_string(),
+            }
+        }
+        DataType::Date32 => dialect.date_literal(&formatter.value(row).to_string()),
+        // Date64 counts milliseconds, so it may carry a time of day
+        DataType::Date64 => {
+            let value = formatter.value(row).to_string();
+            match value.strip_suffix(" 00:00:00") {
+                Some(date) => dialect.date_literal(date),
+                None => dialect.timestamp_literal(&value),
+            }
+        }
+        DataType::Time32(_) | DataType::Time64(_) => {
+            dialect.time_literal(&formatter.value(row).to_string())
+        }
+        DataType::Timestamp(_, _) => dialect.timestamp_literal(&formatter.value(row).to_string()),
+        DataType::Binary => dialect.binary_literal(array.as_binary::<i32>().value(row)),
+        DataType::LargeBinary => dialect.binary_literal(array.as_binary::<i64>().value(row)),
+        DataType::BinaryView => dialect.binary_literal(array.as_binary_view().value(row)),
+        DataType::FixedSizeBinary(_) => {
+            dialect.binary_literal(array.as_fixed_size_binary().value(row))
+        }
+        _ => dialect.quote_string(&formatter.value(row).to_string()),
+    }
+}
@@ -1,2 +1,6 @@
+pub mod ddl;
+pub mod dialect;
 pub mod generator;
+pub mod literal;
 pub mod parse;
+pub mod redact;
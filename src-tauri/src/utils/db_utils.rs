@@ -1,9 +1,13 @@
 use crate::context::schema::AppResult;
+use crate::sql::redact::redact_secrets;
 use crate::utils::app_data_utils::get_app_data_dir;
 use chrono::Local;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::AppHandle;
 
+/// `app_settings` key of the query history switch, history is recorded unless it is "false"
+pub const HISTORY_ENABLED: &str = "history_enabled";
+
 pub fn conn(app: &AppHandle) -> AppResult<Connection> {
     Ok(Connection::open(get_app_data_dir(app)?.join("sqlite.db"))?)
 }
@@ -21,9 +25,58 @@ pub fn init(app: &AppHandle) {
         [],
     )
     .expect("Failed to create query_history");
+
+    // Connection strings themselves live in the OS keyring, only the masked form is stored
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS saved_connections (
+                  name text PRIMARY KEY,
+                  kind text,
+                  display_conn text,
+                  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                  )",
+        [],
+    )
+    .expect("Failed to create saved_connections");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS app_settings (
+                  key text PRIMARY KEY,
+                  value text
+                  )",
+        [],
+    )
+    .expect("Failed to create app_settings");
 }
 
+pub fn get_setting(app: &AppHandle, key: &str) -> AppResult<Option<String>> {
+    Ok(conn(app)?
+        .query_row(
+            "select value from app_settings where key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+pub fn set_setting(app: &AppHandle, key: &str, value: &str) -> AppResult<()> {
+    conn(app)?.execute(
+        "insert into app_settings (key, value) values (?1, ?2)
+                  on conflict (key) do update set value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub fn history_enabled(app: &AppHandle) -> AppResult<bool> {
+    Ok(get_setting(app, HISTORY_ENABLED)?.as_deref() != Some("false"))
+}
+
+/// Records an executed script with its credentials masked, unless history is turned off
 pub fn insert_query_history(app: &AppHandle, sql: &str, status: &str) -> AppResult<()> {
+    if !history_enabled(app)? {
+        return Ok(());
+    }
+
     conn(app)?.execute(
         r#"
                         insert into sql_history ( sql, status, created_at )
@@ -31,7 +84,7 @@ pub fn insert_query_history(app: &AppHandle, sql: &str, status: &str) -> AppResu
                         (?1, ?2, ?3)
                         "#,
         params![
-            sql,
+            redact_secrets(sql),
             status,
             Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
         ],
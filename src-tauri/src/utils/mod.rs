@@ -1,4 +1,5 @@
 pub mod file_utils;
 pub mod date_utils;
 pub mod app_data_utils;
-pub mod db_utils;
\ No newline at end of file
+pub mod db_utils;
+pub mod connection_utils;
\ No newline at end of file
@@ -0,0 +1,154 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use datafusion::common::file_options::csv_writer::CsvWriterOptions;
+use datafusion::common::parsers::CompressionTypeVariant;
+use datafusion::config::CsvOptions;
+use datafusion::dataframe::DataFrame;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CsvExportOptions {
+    pub delimiter: Option<String>,
+    pub quote: Option<String>,
+    pub has_header: Option<bool>,
+    pub null_value: Option<String>,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+    pub line_terminator: Option<String>,
+    pub compression: Option<String>,
+}
+
+impl CsvExportOptions {
+    /// Builds DataFusion's CSV writer options, starting from the file type's delimiter
+    pub fn to_csv_options(&self, default_delimiter: u8) -> AppResult<CsvOptions> {
+        let mut options = CsvOptions::default();
+        options.delimiter = match &self.delimiter {
+            Some(delimiter) => single_byte(delimiter, "delimiter")?,
+            None => default_delimiter,
+        };
+        if let Some(quote) = &self.quote {
+            options.quote = single_byte(quote, "quote")?;
+        }
+        options.has_header = Some(self.has_header.unwrap_or(true));
+        options.null_value = self.null_value.clone();
+        options.date_format = self.date_format.clone();
+        if let Some(timestamp_format) = &self.timestamp_format {
+            options.datetime_format = Some(timestamp_format.clone());
+            options.timestamp_format = Some(timestamp_format.clone());
+        }
+        options.compression = match self.compression.as_deref().map(str::to_lowercase) {
+            None => CompressionTypeVariant::UNCOMPRESSED,
+            Some(compression) => match compression.as_str() {
+                "" | "none" | "uncompressed" => CompressionTypeVariant::UNCOMPRESSED,
+                "gzip" => CompressionTypeVariant::GZIP,
+                "zstd" => CompressionTypeVariant::ZSTD,
+                "bzip2" => CompressionTypeVariant::BZIP2,
+                "xz" => CompressionTypeVariant::XZ,
+                _ => {
+                    return Err(AppError::BadRequest {
+                        message: format!(
+                        "Unsupported CSV compression: '{}'. Supported: none, gzip, zstd, bzip2, xz",
+                        compression
+                    ),
+                    })
+                }
+            },
+        };
+
+        if self.is_crlf()? && options.compression != CompressionTypeVariant::UNCOMPRESSED {
+            return Err(AppError::BadRequest {
+                message: "The \\r\\n line terminator can't be combined with compression"
+                    .to_string(),
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Arrow's CSV writer always ends records with `\n`, so `\r\n` needs its own write path
+    pub fn is_crlf(&self) -> AppResult<bool> {
+        match self.line_terminator.as_deref() {
+            None | Some("\n") | Some("\\n") | Some("lf") | Some("LF") => Ok(false),
+            Some("\r\n") | Some("\\r\\n") | Some("crlf") | Some("CRLF") => Ok(true),
+            Some(terminator) => Err(AppError::BadRequest {
+                message: format!(
+                    "Unsupported line terminator: '{}'. Supported: \\n, \\r\\n",
+                    terminator.escape_default()
+                ),
+            }),
+        }
+    }
+
+    /// File name suffix added by the chosen compression, e.g. `.gz`
+    pub fn extension_suffix(&self) -> AppResult<&'static str> {
+        Ok(match self.to_csv_options(b',')?.compression {
+            CompressionTypeVariant::GZIP => ".gz",
+            CompressionTypeVariant::ZSTD => ".zst",
+            CompressionTypeVariant::BZIP2 => ".bz2",
+            CompressionTypeVariant::XZ => ".xz",
+            CompressionTypeVariant::UNCOMPRESSED => "",
+        })
+    }
+}
+
+/// Accepts a single character, or an escaped tab such as `\t`
+fn single_byte(value: &str, name: &str) -> AppResult<u8> {
+    match value {
+        "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 => Ok(value.as_bytes()[0]),
+        _ => Err(AppError::BadRequest {
+            message: format!("Invalid {}: '{}', expected a single character", name, value),
+        }),
+    }
+}
+
+/// Writes the DataFrame as CSV with `\r\n` record terminators, using the same
+/// Arrow writer settings DataFusion derives from `options`.
+pub async fn write_csv_crlf(df: DataFrame, path: &str, options: &CsvOptions) -> AppResult<()> {
+    let writer_options = CsvWriterOptions::try_from(options)?;
+    let mut stream = df.execute_stream().await?;
+    let mut writer = writer_options.writer_options.build(CrlfWriter {
+        inner: BufWriter::new(File::create(path)?),
+        quote: options.quote,
+        in_quotes: false,
+    });
+
+    while let Some(batch) = stream.next().await {
+        writer.write(&batch?)?;
+    }
+    writer.into_inner().flush()?;
+
+    Ok(())
+}
+
+/// Turns the `\n` after each record into `\r\n`, leaving newlines inside quoted values alone
+struct CrlfWriter<W: Write> {
+    inner: W,
+    quote: u8,
+    in_quotes: bool,
+}
+
+impl<W: Write> Write for CrlfWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut start = 0;
+        for (i, byte) in buf.iter().enumerate() {
+            if *byte == self.quote {
+                self.in_quotes = !self.in_quotes;
+            } else if *byte == b'\n' && !self.in_quotes {
+                self.inner.write_all(&buf[start..i])?;
+                self.inner.write_all(b"\r\n")?;
+                start = i + 1;
+            }
+        }
+        self.inner.write_all(&buf[start..])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
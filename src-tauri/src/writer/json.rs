@@ -0,0 +1,27 @@
+use crate::context::schema::AppResult;
+use datafusion::arrow::json::ArrayWriter;
+use datafusion::dataframe::DataFrame;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// JSON and NDJSON exports take no options, so any field sent is rejected
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonExportOptions {}
+
+/// Writes the DataFrame as a single JSON array, batch by batch.
+/// DataFusion's own JSON sink only produces newline-delimited JSON.
+pub async fn write_json_array(df: DataFrame, path: &str) -> AppResult<()> {
+    let mut stream = df.execute_stream().await?;
+    let mut writer = ArrayWriter::new(BufWriter::new(File::create(path)?));
+
+    while let Some(batch) = stream.next().await {
+        writer.write(&batch?)?;
+    }
+    writer.finish()?;
+    writer.into_inner().flush()?;
+
+    Ok(())
+}
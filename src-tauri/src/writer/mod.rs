@@ -0,0 +1,155 @@
+use crate::context::error::AppError;
+use crate::context::schema::AppResult;
+use datafusion::dataframe::DataFrameWriteOptions;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::{PathBuf, MAIN_SEPARATOR};
+
+pub mod csv;
+pub mod json;
+pub mod mysql;
+pub mod xlsx;
+
+/// What to do when the export destination already exists
+pub enum WriteMode {
+    Overwrite,
+    Append,
+    Fail,
+}
+
+impl WriteMode {
+    pub fn from_str(s: &str) -> AppResult<Self> {
+        match s.to_lowercase().as_str() {
+            "overwrite" => Ok(WriteMode::Overwrite),
+            "append" => Ok(WriteMode::Append),
+            "fail" => Ok(WriteMode::Fail),
+            _ => Err(AppError::BadRequest {
+                message: format!(
+                    "Invalid write mode: '{}'. Please use 'overwrite', 'append' or 'fail'.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// Where an export lands: a single file, or a directory DataFusion fills with part files
+pub enum ExportTarget {
+    File(PathBuf),
+    Directory(PathBuf),
+}
+
+impl ExportTarget {
+    /// Resolves `output_path` (a file or a directory) against the default download
+    /// directory. `file_name` is used when only a directory is known.
+    pub fn resolve(
+        output_path: Option<String>,
+        file_name: String,
+        partitioned: bool,
+    ) -> AppResult<Self> {
+        let (path, is_directory) = match output_path {
+            Some(output_path) => {
+                let is_directory =
+                    output_path.ends_with(['/', '\\']) || PathBuf::from(&output_path).is_dir();
+                (PathBuf::from(output_path), is_directory)
+            }
+            None => {
+                let downloads_dir = dirs::download_dir().ok_or_else(|| AppError::BadRequest {
+                    message: "Couldn't find the current working directory".to_string(),
+                })?;
+                (downloads_dir, true)
+            }
+        };
+
+        Ok(match (partitioned, is_directory) {
+            (true, true) => ExportTarget::Directory(
+                path.join(file_name.split('.').next().unwrap_or(&file_name)),
+            ),
+            (true, false) => ExportTarget::Directory(path),
+            (false, true) => ExportTarget::File(path.join(file_name)),
+            (false, false) => ExportTarget::File(path),
+        })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            ExportTarget::File(path) | ExportTarget::Directory(path) => path,
+        }
+    }
+
+    /// Applies the write mode to an existing destination.
+    /// Returns `true` when new data has to be appended to an existing file.
+    pub fn prepare(&self, write_mode: &WriteMode) -> AppResult<bool> {
+        let path = self.path();
+        let exists = match self {
+            ExportTarget::File(path) => path.exists(),
+            ExportTarget::Directory(path) => path.exists() && fs::read_dir(path)?.next().is_some(),
+        };
+
+        if exists {
+            match write_mode {
+                WriteMode::Fail => {
+                    return Err(AppError::BadRequest {
+                        message: format!("'{}' already exists", path.display()),
+                    })
+                }
+                WriteMode::Overwrite => match self {
+                    ExportTarget::File(path) => fs::remove_file(path)?,
+                    ExportTarget::Directory(path) => fs::remove_dir_all(path)?,
+                },
+                // DataFusion adds uniquely named part files to a directory
+                WriteMode::Append => {}
+            }
+        }
+
+        match self {
+            ExportTarget::File(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            ExportTarget::Directory(path) => fs::create_dir_all(path)?,
+        }
+
+        Ok(exists
+            && matches!(
+                (self, write_mode),
+                (ExportTarget::File(_), WriteMode::Append)
+            ))
+    }
+
+    /// The path handed to the writers. Appends go to a temporary sibling file first,
+    /// and directories get a trailing separator so DataFusion treats them as one.
+    pub fn write_path(&self, appending: bool) -> String {
+        match self {
+            ExportTarget::File(path) if appending => {
+                format!("{}.easydb-tmp", path.display())
+            }
+            ExportTarget::File(path) => path.display().to_string(),
+            ExportTarget::Directory(path) => format!("{}{}", path.display(), MAIN_SEPARATOR),
+        }
+    }
+
+    /// DataFusion's writers create a directory of part files for a path without an
+    /// extension, so a single file target asks for single file output explicitly
+    /// and every format writes `output_path` the same way.
+    pub fn write_options(&self, partition_by: Vec<String>) -> DataFrameWriteOptions {
+        DataFrameWriteOptions::new()
+            .with_single_file_output(matches!(self, ExportTarget::File(_)))
+            .with_partition_by(partition_by)
+    }
+
+    /// Moves the temporary file written by an append onto the end of the target.
+    /// The temporary file is removed whether or not the copy succeeds.
+    pub fn finish_append(&self, write_path: &str) -> AppResult<()> {
+        let copied = OpenOptions::new()
+            .append(true)
+            .open(self.path())
+            .and_then(|mut target| io::copy(&mut fs::File::open(write_path)?, &mut target));
+        let removed = fs::remove_file(write_path);
+        copied?;
+        removed?;
+        Ok(())
+    }
+}
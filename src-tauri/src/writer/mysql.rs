@@ -0,0 +1,218 @@
+use crate::context::schema::AppResult;
+use crate::sql::ddl::generate_create_table;
+use crate::sql::dialect::{MySqlDialect, SqlDialect};
+use crate::sql::generator::RowChunker;
+use crate::sql::literal::extract_literals;
+use datafusion::dataframe::DataFrame;
+use datafusion_table_providers::sql::db_connection_pool::mysqlpool::MySQLConnectionPool;
+use futures::StreamExt;
+use mysql_async::prelude::Queryable;
+use mysql_async::{Conn, Transaction, TxOpts};
+
+/// Rows per INSERT statement when no batch size is given
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+pub struct MySqlExportOptions {
+    table_name: String,
+    create_table: bool,
+    truncate: bool,
+    batch_size: usize,
+}
+
+impl MySqlExportOptions {
+    pub fn new(table_name: String) -> Self {
+        Self {
+            table_name,
+            create_table: false,
+            truncate: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Creates the table from the result schema when it doesn't exist yet
+    pub fn with_create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    /// Deletes the existing rows first, inside the same transaction as the load
+    pub fn with_truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+/// Streams the DataFrame into a MySQL table as multi-row INSERTs, all in one
+/// transaction that is rolled back if any batch fails. A table created for the load
+/// is dropped again when it fails or is cancelled, since MySQL commits DDL outside of
+/// transactions. Returns the number of rows written.
+pub async fn write_mysql<P>(
+    df: DataFrame,
+    pool: &MySQLConnectionPool,
+    options: &MySqlExportOptions,
+    on_progress: P,
+) -> AppResult<usize>
+where
+    P: FnMut(usize),
+{
+    let connection = pool.connect_direct().await?;
+    let mut conn = connection.conn.lock().await;
+
+    let mut created = CreatedTable {
+        pool: pool.clone(),
+        table: None,
+    };
+    let result = load(&mut conn, df, options, &mut created, on_progress).await;
+    match &result {
+        Ok(_) => created.table = None,
+        Err(_) => {
+            if let Some(table) = created.table.take() {
+                let _ = conn.query_drop(format!("DROP TABLE {}", table)).await;
+            }
+        }
+    }
+    result
+}
+
+/// A table created for a load that hasn't finished. Dropping the guard before the load
+/// completes, e.g. when the export is cancelled and its future dropped, drops the
+/// table in the background.
+struct CreatedTable {
+    pool: MySQLConnectionPool,
+    table: Option<String>,
+}
+
+impl Drop for CreatedTable {
+    fn drop(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let pool = self.pool.clone();
+        runtime.spawn(async move {
+            if let Ok(connection) = pool.connect_direct().await {
+                let mut conn = connection.conn.lock().await;
+                let _ = conn.query_drop(format!("DROP TABLE {}", table)).await;
+            }
+        });
+    }
+}
+
+async fn load<P>(
+    conn: &mut Conn,
+    df: DataFrame,
+    options: &MySqlExportOptions,
+    created: &mut CreatedTable,
+    on_progress: P,
+) -> AppResult<usize>
+where
+    P: FnMut(usize),
+{
+    let table = MySqlDialect.quote_table_name(&options.table_name);
+
+    if options.create_table && !table_exists(conn, &options.table_name).await? {
+        let mut ddl = Vec::new();
+        generate_create_table(&df, &options.table_name, &MySqlDialect, &mut ddl).await?;
+        conn.query_drop(String::from_utf8_lossy(&ddl).into_owned())
+            .await?;
+        created.table = Some(table.clone());
+    }
+
+    let mut tx = conn.start_transaction(TxOpts::default()).await?;
+    match insert_rows(&mut tx, &table, df, options, on_progress).await {
+        Ok(rows_written) => {
+            tx.commit().await?;
+            Ok(rows_written)
+        }
+        Err(err) => {
+            let _ = tx.rollback().await;
+            Err(err)
+        }
+    }
+}
+
+/// Looks the table up in the schema of a qualified `db.table` name, or in the
+/// connection's default database
+async fn table_exists(conn: &mut Conn, table_name: &str) -> AppResult<bool> {
+    let (schema, table_name) = match table_name.split_once('.') {
+        Some((schema, table_name)) => (Some(schema), table_name),
+        None => (None, table_name),
+    };
+    let count: Option<u64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM information_schema.tables \
+             WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?",
+            (schema, table_name),
+        )
+        .await?;
+    Ok(count.unwrap_or(0) > 0)
+}
+
+async fn insert_rows<P>(
+    tx: &mut Transaction<'_>,
+    table: &str,
+    df: DataFrame,
+    options: &MySqlExportOptions,
+    mut on_progress: P,
+) -> AppResult<usize>
+where
+    P: FnMut(usize),
+{
+    if options.truncate {
+        tx.query_drop(format!("DELETE FROM {}", table)).await?;
+    }
+
+    let columns = df
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| MySqlDialect.quote_identifier(f.name()))
+        .collect::<Vec<String>>();
+
+    let mut chunker = RowChunker::new(options.batch_size, &MySqlDialect);
+    let mut statements: Vec<String> = Vec::new();
+    let mut rows_written = 0;
+
+    // Statements of a batch are built synchronously, then sent before the next batch
+    let mut stream = df.execute_stream().await?;
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        extract_literals(&batch, &MySqlDialect, |row| {
+            chunker.push(row, &mut |rows: &[Vec<String>]| {
+                push_insert(&mut statements, table, &columns, rows)
+            })
+        })?;
+        for statement in statements.drain(..) {
+            tx.query_drop(statement).await?;
+        }
+        rows_written += batch.num_rows();
+        on_progress(rows_written);
+    }
+
+    chunker
+        .finish(&mut |rows: &[Vec<String>]| push_insert(&mut statements, table, &columns, rows))?;
+    for statement in statements.drain(..) {
+        tx.query_drop(statement).await?;
+    }
+
+    Ok(rows_written)
+}
+
+fn push_insert(
+    statements: &mut Vec<String>,
+    table: &str,
+    columns: &[String],
+    rows: &[Vec<String>],
+) -> AppResult<()> {
+    let mut statement = Vec::new();
+    MySqlDialect.write_insert(&mut statement, table, columns, rows)?;
+    statements.push(String::from_utf8_lossy(&statement).into_owned());
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use crate::context::schema::AppResult;
+use datafusion::arrow::array::{
+    Array, AsArray, BooleanArray, Float64Array, Int64Array, UInt64Array,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Float64Type, Int64Type, UInt64Type};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+use datafusion::dataframe::DataFrame;
+use futures::StreamExt;
+use rust_xlsxwriter::{ColNum, Color, Format, FormatBorder, RowNum, Workbook, Worksheet};
+use serde::Deserialize;
+
+/// Largest integer a number cell (an f64) holds exactly
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct XlsxExportOptions {
+    pub sheet_name: Option<String>,
+    pub style_header: Option<bool>,
+}
+
+pub struct XlsxWriter {
+    path: String,
+    sheet_name: Option<String>,
+    style_header: bool,
+}
+
+enum CellValues<'a> {
+    Number(Float64Array),
+    /// 64-bit integers become text when they don't fit a number cell exactly
+    Int64(&'a Int64Array),
+    UInt64(&'a UInt64Array),
+    Boolean(&'a BooleanArray),
+    Text(ArrayFormatter<'a>),
+}
+
+impl XlsxWriter {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            sheet_name: None,
+            style_header: true,
+        }
+    }
+
+    pub fn with_sheet_name(mut self, sheet_name: String) -> Self {
+        self.sheet_name = Some(sheet_name);
+        self
+    }
+
+    pub fn with_style_header(mut self, style_header: bool) -> Self {
+        self.style_header = style_header;
+        self
+    }
+
+    pub async fn write(self, df: DataFrame) -> AppResult<()> {
+        let mut stream = df.execute_stream().await?;
+        let schema = stream.schema();
+
+        let mut workbook = Workbook::new();
+        // Rows are flushed to disk as they are written, so large results stay out of memory
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        if let Some(sheet_name) = &self.sheet_name {
+            worksheet.set_name(sheet_name)?;
+        }
+
+        let header_format = Format::new()
+            .set_bold()
+            .set_background_color(Color::RGB(0xD9E1F2))
+            .set_border_bottom(FormatBorder::Thin);
+        for (col, field) in schema.fields().iter().enumerate() {
+            if self.style_header {
+                worksheet.write_string_with_format(
+                    0,
+                    col as ColNum,
+                    field.name(),
+                    &header_format,
+                )?;
+            } else {
+                worksheet.write_string(0, col as ColNum, field.name())?;
+            }
+        }
+        if self.style_header {
+            worksheet.set_freeze_panes(1, 0)?;
+        }
+
+        let mut row: RowNum = 1;
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            write_batch(worksheet, &batch, row)?;
+            row += batch.num_rows() as RowNum;
+        }
+
+        workbook.save(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Writes a batch row by row, as constant memory mode requires rows in order
+fn write_batch(worksheet: &mut Worksheet, batch: &RecordBatch, first_row: RowNum) -> AppResult<()> {
+    let options = FormatOptions::default();
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|array| -> AppResult<CellValues> {
+            Ok(match array.data_type() {
+                DataType::Boolean => CellValues::Boolean(array.as_boolean()),
+                DataType::Int64 => CellValues::Int64(array.as_primitive::<Int64Type>()),
+                DataType::UInt64 => CellValues::UInt64(array.as_primitive::<UInt64Type>()),
+                // Decimals are written as text to keep all of their digits
+                data_type if data_type.is_integer() || data_type.is_floating() => {
+                    CellValues::Number(
+                        cast(array, &DataType::Float64)?
+                            .as_primitive::<Float64Type>()
+                            .clone(),
+                    )
+                }
+                _ => CellValues::Text(ArrayFormatter::try_new(array.as_ref(), &options)?),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for index in 0..batch.num_rows() {
+        let row = first_row + index as RowNum;
+        for (col, values) in columns.iter().enumerate() {
+            if batch.column(col).is_null(index) {
+                continue;
+            }
+            let col = col as ColNum;
+            match values {
+                CellValues::Number(values) => {
+                    worksheet.write_number(row, col, values.value(index))?;
+                }
+                CellValues::Int64(values) => {
+                    let value = values.value(index);
+                    if value.unsigned_abs() <= MAX_EXACT_INTEGER {
+                        worksheet.write_number(row, col, value as f64)?;
+                    } else {
+                        worksheet.write_string(row, col, value.to_string())?;
+                    }
+                }
+                CellValues::UInt64(values) => {
+                    let value = values.value(index);
+                    if value <= MAX_EXACT_INTEGER {
+                        worksheet.write_number(row, col, value as f64)?;
+                    } else {
+                        worksheet.write_string(row, col, value.to_string())?;
+                    }
+                }
+                CellValues::Boolean(values) => {
+                    worksheet.write_boolean(row, col, values.value(index))?;
+                }
+                CellValues::Text(formatter) => {
+                    worksheet.write_string(row, col, formatter.value(index).to_string())?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 // use app_lib::context::context::{collect, get_sql_context, register};
 // use app_lib::context::schema::AppResult;
 
-use app_lib::context::context::{collect, get_sql_context, register};
+use app_lib::context::context::{collect_script, get_sql_context, register};
 use app_lib::context::schema::AppResult;
 
 // use app_lib::context::context::{collect, register};
@@ -74,8 +74,8 @@ FROM
     "#;
 
     let mut context = get_sql_context();
-    let new_sql = register(&mut context, &sql, Some(200), None).await?;
-    let df = collect(&mut context, &new_sql).await?;
+    let statements = register(&mut context, &sql, Some(200), None).await?;
+    let df = collect_script(&mut context, &statements).await?;
 
     Ok(())
 }
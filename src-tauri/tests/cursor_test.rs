@@ -0,0 +1,55 @@
+use app_lib::context::context::get_sql_context;
+use app_lib::context::cursor::{QueryCursor, QueryCursors, MAX_OPEN_CURSORS};
+use app_lib::context::schema::AppResult;
+
+#[tokio::test]
+async fn test_query_cursor_paging() -> AppResult<()> {
+    let context = get_sql_context();
+
+    let df = context
+        .sql("SELECT value FROM generate_series(1, 10)")
+        .await?;
+    let mut cursor = QueryCursor::open(df).await?;
+
+    let mut pages = Vec::new();
+    while !cursor.is_exhausted() {
+        let records = cursor.next_page(4).await?;
+        pages.push(records.iter().map(|r| r.num_rows()).sum::<usize>());
+    }
+    assert_eq!(pages, vec![4, 4, 2]);
+
+    // The page that takes the last rows reports the end, no empty page follows
+    let df = context
+        .sql("SELECT value FROM generate_series(1, 8)")
+        .await?;
+    let mut cursor = QueryCursor::open(df).await?;
+    let records = cursor.next_page(4).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 4);
+    assert!(!cursor.is_exhausted());
+    let records = cursor.next_page(4).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 4);
+    assert!(cursor.is_exhausted());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_cursors_cap() -> AppResult<()> {
+    let context = get_sql_context();
+    let cursors = QueryCursors::default();
+
+    let mut handles = Vec::new();
+    for _ in 0..=MAX_OPEN_CURSORS {
+        let df = context.sql("SELECT 1").await?;
+        handles.push(cursors.insert(QueryCursor::open(df).await?).await);
+    }
+
+    // The least recently used cursor was closed to make room for the last one
+    assert!(cursors.get(handles[0]).await.is_err());
+    assert!(cursors.get(handles[MAX_OPEN_CURSORS]).await.is_ok());
+
+    cursors.remove(handles[1]).await;
+    assert!(cursors.get(handles[1]).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use app_lib::context::context::{collect_script, get_sql_context, register};
+use app_lib::context::schema::AppResult;
+use datafusion::arrow::array::AsArray;
+use datafusion::arrow::datatypes::{DataType, Float64Type, Int64Type, TimeUnit};
+use rust_xlsxwriter::Workbook;
+use std::fs;
+
+#[tokio::test]
+async fn test_read_excel_streaming() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_excel_test.xlsx");
+    let _ = fs::remove_file(&path);
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.write_string(0, 0, "id")?;
+    worksheet.write_string(0, 1, "name")?;
+    for id in 1..=50u32 {
+        worksheet.write_number(id, 0, id as f64)?;
+        worksheet.write_string(id, 1, format!("n{}", id))?;
+    }
+    workbook.save(&path)?;
+
+    let mut context = get_sql_context();
+
+    let sql = format!(
+        "SELECT name FROM read_excel('{}', batch_size => 8) LIMIT 5",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 5);
+    assert_eq!(records[0].num_columns(), 1);
+
+    let sql = format!(
+        "SELECT count(*) FROM read_excel('{}') WHERE id > 40",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    let count = records[0].column(0).as_primitive::<Int64Type>().value(0);
+    assert_eq!(count, 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_excel_layout() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_excel_layout_test.xlsx");
+    let _ = fs::remove_file(&path);
+
+    // Title banner, header on row 3, a units row and a totals footer
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.write_string(0, 0, "Sales report")?;
+    worksheet.write_string(2, 0, "id")?;
+    worksheet.write_string(2, 1, "region")?;
+    worksheet.write_string(2, 2, "amount")?;
+    worksheet.write_string(3, 2, "USD")?;
+    for id in 1..=10u32 {
+        worksheet.write_number(id + 3, 0, id as f64)?;
+        worksheet.write_string(id + 3, 1, format!("r{}", id % 2))?;
+        worksheet.write_number(id + 3, 2, (id * 10) as f64)?;
+    }
+    worksheet.write_string(14, 0, "total")?;
+    worksheet.write_number(14, 2, 550.0)?;
+    workbook.save(&path)?;
+
+    let mut context = get_sql_context();
+
+    let sql = format!(
+        "SELECT count(*), sum(amount) FROM read_excel('{}', header_row => 3, skip_rows => 1, skip_footer => 1)",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(
+        records[0].column(0).as_primitive::<Int64Type>().value(0),
+        10
+    );
+    assert_eq!(
+        records[0].column(1).as_primitive::<Float64Type>().value(0),
+        550.0
+    );
+
+    let sql = format!(
+        "SELECT count(*) FROM read_excel('{}', range => 'B5:C14', has_header => false) WHERE t2 > 50",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_excel_sheet_union() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_excel_sheets_test.xlsx");
+    let _ = fs::remove_file(&path);
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet().set_name("2024-01")?;
+    worksheet.write_string(0, 0, "id")?;
+    worksheet.write_string(0, 1, "amount")?;
+    for id in 1..=3u32 {
+        worksheet.write_number(id, 0, id as f64)?;
+        worksheet.write_number(id, 1, (id * 10) as f64)?;
+    }
+    // Columns in another order, plus one the first sheet doesn't have
+    let worksheet = workbook.add_worksheet().set_name("2024-02")?;
+    worksheet.write_string(0, 0, "amount")?;
+    worksheet.write_string(0, 1, "id")?;
+    worksheet.write_string(0, 2, "note")?;
+    for id in 4..=5u32 {
+        worksheet.write_number(id - 3, 0, (id * 10) as f64)?;
+        worksheet.write_number(id - 3, 1, id as f64)?;
+        worksheet.write_string(id - 3, 2, "late")?;
+    }
+    let worksheet = workbook.add_worksheet().set_name("Notes")?;
+    worksheet.write_string(0, 0, "text")?;
+    worksheet.write_string(1, 0, "draft")?;
+    workbook.save(&path)?;
+
+    let mut context = get_sql_context();
+
+    let sql = format!(
+        "SELECT count(*), count(note), sum(amount) FROM read_excel('{}', sheet_name => '2024-*')",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 5);
+    assert_eq!(records[0].column(1).as_primitive::<Int64Type>().value(0), 2);
+    assert_eq!(
+        records[0].column(2).as_primitive::<Float64Type>().value(0),
+        150.0
+    );
+
+    let sql = format!(
+        "SELECT count(*) FROM read_excel('{}', sheet_name => '*') WHERE __sheet = '2024-02'",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_excel_legacy_formats_dates() -> AppResult<()> {
+    let mut context = get_sql_context();
+
+    // xls stores dates as formatted numbers, ods as ISO 8601 text (dates without a time part)
+    for fixture in ["dates.xls", "dates.ods"] {
+        let path = format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), fixture);
+
+        let sql = format!("SELECT day, at FROM read_excel('{}')", path);
+        let statements = register(&mut context, &sql, None, None).await?;
+        let records = collect_script(&mut context, &statements).await?;
+        let schema = records[0].schema();
+        for field in schema.fields() {
+            assert_eq!(
+                field.data_type(),
+                &DataType::Timestamp(TimeUnit::Nanosecond, None),
+                "{} {}",
+                fixture,
+                field.name()
+            );
+        }
+
+        let sql = format!(
+            "SELECT count(*) FROM read_excel('{}') \
+             WHERE day = TIMESTAMP '2024-02-29 00:00:00' AND at = TIMESTAMP '2024-02-29 23:59:59'",
+            path
+        );
+        let statements = register(&mut context, &sql, None, None).await?;
+        let records = collect_script(&mut context, &statements).await?;
+        assert_eq!(
+            records[0].column(0).as_primitive::<Int64Type>().value(0),
+            1,
+            "{}",
+            fixture
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use app_lib::context::context::{get_script_data_frame, get_sql_context, register};
+use app_lib::context::schema::AppResult;
+use app_lib::writer::{ExportTarget, WriteMode};
+use std::fs;
+
+#[tokio::test]
+async fn test_export_to_extensionless_path() -> AppResult<()> {
+    let mut context = get_sql_context();
+    let statements = register(
+        &mut context,
+        "SELECT * FROM (VALUES (1, 'a'), (2, 'b')) AS t(id, name)",
+        None,
+        None,
+    )
+    .await?;
+
+    for file_type in ["csv", "parquet", "ndjson"] {
+        let path = std::env::temp_dir().join(format!("easydb_export_{}_out", file_type));
+        let _ = fs::remove_dir_all(&path);
+        let _ = fs::remove_file(&path);
+
+        // No extension and nothing there yet: still a single file, as for the other formats
+        let target = ExportTarget::resolve(
+            Some(path.display().to_string()),
+            "unused".to_string(),
+            false,
+        )?;
+        let appending = target.prepare(&WriteMode::Overwrite)?;
+        let file_path = target.write_path(appending);
+        let write_options = target.write_options(Vec::new());
+
+        let df = get_script_data_frame(&mut context, &statements).await?;
+        match file_type {
+            "csv" => df.write_csv(&file_path, write_options, None).await?,
+            "parquet" => df.write_parquet(&file_path, write_options, None).await?,
+            _ => df.write_json(&file_path, write_options, None).await?,
+        };
+        assert!(path.is_file(), "{} export created a directory", file_type);
+    }
+
+    Ok(())
+}
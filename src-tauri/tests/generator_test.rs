@@ -0,0 +1,188 @@
+use app_lib::context::context::{get_script_data_frame, get_sql_context, register};
+use app_lib::context::schema::AppResult;
+use app_lib::sql::ddl::generate_create_table;
+use app_lib::sql::dialect::{MsSqlDialect, MySqlDialect, OracleDialect, PostgreSqlDialect};
+use app_lib::sql::generator::{
+    generate_sql_deletes, generate_sql_inserts, generate_sql_update, generate_sql_upserts,
+    UpdateOptions,
+};
+
+#[tokio::test]
+async fn test_generate_sql_inserts_literals() -> AppResult<()> {
+    let sql = r#"
+SELECT '00123' AS zip, 'NULL' AS label, 'a\b''c' AS path, true AS active,
+       CAST(1.50 AS DECIMAL(10, 2)) AS price, CAST(NULL AS INT) AS missing
+    "#;
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, sql, None, None).await?;
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    let rows = generate_sql_inserts(df, "t", 100, &MySqlDialect, &mut out, |_| {}).await?;
+    assert_eq!(rows, 1);
+    assert!(String::from_utf8_lossy(&out).contains(r"('00123', 'NULL', 'a\\b''c', 1, 1.50, NULL);"));
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_inserts(df, "t", 100, &PostgreSqlDialect, &mut out, |_| {}).await?;
+    assert!(
+        String::from_utf8_lossy(&out).contains(r"('00123', 'NULL', 'a\b''c', TRUE, 1.50, NULL);")
+    );
+
+    let sql = r#"
+SELECT arrow_cast(TIMESTAMP '2024-01-01 00:00:00', 'Date64') AS day,
+       arrow_cast(TIMESTAMP '2024-01-01 10:30:00', 'Date64') AS at
+    "#;
+    let statements = register(&mut context, sql, None, None).await?;
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_inserts(df, "t", 100, &PostgreSqlDialect, &mut out, |_| {}).await?;
+    assert!(String::from_utf8_lossy(&out)
+        .contains("(DATE '2024-01-01', TIMESTAMP '2024-01-01 10:30:00');"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_sql_inserts_dialects() -> AppResult<()> {
+    let sql = "SELECT * FROM (VALUES (1, 'a'), (2, 'b'), (3, 'c')) AS t(id, name)";
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, sql, None, None).await?;
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_inserts(df, "t", 2, &MsSqlDialect, &mut out, |_| {}).await?;
+    let script = String::from_utf8_lossy(&out);
+    assert_eq!(
+        script
+            .matches("INSERT INTO [t] ([id], [name]) VALUES")
+            .count(),
+        2
+    );
+    assert!(script.contains("(3, N'c');"));
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_inserts(df, "t", 100, &OracleDialect, &mut out, |_| {}).await?;
+    let script = String::from_utf8_lossy(&out);
+    assert!(script.starts_with("INSERT ALL\n  INTO \"t\" (\"id\", \"name\") VALUES (1, 'a')"));
+    assert!(script.ends_with("SELECT 1 FROM DUAL;"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_sql_upserts_and_deletes() -> AppResult<()> {
+    let sql = "SELECT * FROM (VALUES (1, 'a'), (2, 'b')) AS t(id, name)";
+    let keys = ["id".to_string()];
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, sql, None, None).await?;
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_upserts(df, "t", &keys, 100, &PostgreSqlDialect, &mut out, |_| {}).await?;
+    assert!(String::from_utf8_lossy(&out)
+        .ends_with("ON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\";"));
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_upserts(df, "t", &keys, 100, &MySqlDialect, &mut out, |_| {}).await?;
+    assert!(
+        String::from_utf8_lossy(&out).ends_with("ON DUPLICATE KEY UPDATE `name` = VALUES(`name`);")
+    );
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_upserts(df, "t", &keys, 100, &MsSqlDialect, &mut out, |_| {}).await?;
+    let script = String::from_utf8_lossy(&out);
+    assert!(script.starts_with("MERGE INTO [t] AS target"));
+    assert!(script.contains("ON target.[id] = source.[id]"));
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_deletes(df, "t", &keys, 100, &MySqlDialect, &mut out, |_| {}).await?;
+    assert_eq!(
+        String::from_utf8_lossy(&out),
+        "DELETE FROM `t` WHERE `id` IN (1, 2);"
+    );
+
+    // NULL keys can't be matched with IN
+    let sql = "SELECT * FROM (VALUES (1, 'a'), (NULL, 'b'), (3, 'c')) AS t(id, name)";
+    let statements = register(&mut context, sql, None, None).await?;
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_deletes(df, "t", &keys, 100, &MySqlDialect, &mut out, |_| {}).await?;
+    assert_eq!(
+        String::from_utf8_lossy(&out),
+        "DELETE FROM `t` WHERE `id` IN (1, 3);\nDELETE FROM `t` WHERE `id` IS NULL;"
+    );
+
+    // Oracle rejects IN lists longer than 1000 values
+    let statements = register(
+        &mut context,
+        "SELECT value AS id FROM generate_series(1, 1500)",
+        None,
+        None,
+    )
+    .await?;
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_deletes(df, "t", &keys, 5000, &OracleDialect, &mut out, |_| {}).await?;
+    assert_eq!(
+        String::from_utf8_lossy(&out)
+            .matches("DELETE FROM \"t\" WHERE \"id\" IN (")
+            .count(),
+        2
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_sql_update_composite_keys() -> AppResult<()> {
+    let sql =
+        "SELECT * FROM (VALUES (1, 'x', 'a', 10), (2, 'y', NULL, 20)) AS t(id, region, name, qty)";
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, sql, None, None).await?;
+
+    let options = UpdateOptions::new(vec!["id".to_string(), "region".to_string()])
+        .with_exclude_columns(vec!["qty".to_string()])
+        .with_skip_nulls(true);
+
+    let mut out = Vec::new();
+    let df = get_script_data_frame(&mut context, &statements).await?;
+    generate_sql_update(df, "t", &options, &PostgreSqlDialect, &mut out, |_| {}).await?;
+    assert_eq!(
+        String::from_utf8_lossy(&out),
+        r#"UPDATE "t" SET "name" = 'a' WHERE "id" = 1 AND "region" = 'x';"#
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_create_table() -> AppResult<()> {
+    let sql = "SELECT * FROM (VALUES (1, 'abc', CAST(1.5 AS DECIMAL(12, 2))), (2, 'abcdef', NULL)) AS t(id, name, price)";
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, sql, None, None).await?;
+    let df = get_script_data_frame(&mut context, &statements).await?;
+
+    let mut out = Vec::new();
+    generate_create_table(&df, "t", &MySqlDialect, &mut out).await?;
+    let ddl = String::from_utf8_lossy(&out);
+    assert!(ddl.starts_with("CREATE TABLE `t` ("));
+    assert!(ddl.contains("`name` VARCHAR(6)"));
+    assert!(ddl.contains("`price` DECIMAL(12, 2)"));
+
+    // Qualified names are quoted part by part
+    let mut out = Vec::new();
+    generate_create_table(&df, "sales.t", &MySqlDialect, &mut out).await?;
+    assert!(String::from_utf8_lossy(&out).starts_with("CREATE TABLE `sales`.`t` ("));
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use app_lib::context::context::{collect_script, get_sql_context, register};
+use app_lib::context::schema::AppResult;
+use std::fs;
+
+#[tokio::test]
+async fn test_read_json_records_path() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_json_test.json");
+    fs::write(
+        &path,
+        r#"{"data": {"items": [
+            {"id": 1, "profile": {"name": "a"}, "tags": ["x", "y"]},
+            {"id": 2, "profile": {"name": "b"}, "tags": []}
+        ]}}"#,
+    )?;
+
+    let sql = format!(
+        "SELECT id, profile['name'] AS name FROM read_json('{}', records_path => '$.data.items') ORDER BY id",
+        path.display()
+    );
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use app_lib::sql::redact::redact_secrets;
+
+#[test]
+fn test_redact_connection_urls() {
+    let sql = "-- replica\nSELECT *\nFROM read_mysql('users', conn => 'mysql://root:s3cret@db:3306/app')\nWHERE name = 'it''s'";
+    assert_eq!(
+        redact_secrets(sql),
+        "-- replica\nSELECT *\nFROM read_mysql('users', conn => 'mysql://root:***@db:3306/app')\nWHERE name = 'it''s'"
+    );
+
+    let sql =
+        "SELECT * FROM read_postgres('t', conn => 'host=db user=app password=s3cret dbname=app')";
+    assert_eq!(
+        redact_secrets(sql),
+        "SELECT * FROM read_postgres('t', conn => 'host=db user=app password=*** dbname=app')"
+    );
+}
+
+#[test]
+fn test_redact_secret_args() {
+    let sql = "SELECT * FROM read_api('https://example.com', API_KEY => 'sk-123', page => '1')";
+    assert_eq!(
+        redact_secrets(sql),
+        "SELECT * FROM read_api('https://example.com', API_KEY => '***', page => '1')"
+    );
+
+    // Left untouched when there is nothing to mask
+    let sql = "SELECT * FROM read_mysql('users', conn => @prod_replica)";
+    assert_eq!(redact_secrets(sql), sql);
+}
+
+#[test]
+fn test_redact_untokenizable_script() {
+    // The unterminated string makes tokenizing fail, the raw text is masked instead
+    let sql = "SELECT * FROM read_api('mysql://root:s3cret@db/app', Password => 'it''s secret') WHERE name = 'oops";
+    assert_eq!(
+        redact_secrets(sql),
+        "SELECT * FROM read_api('mysql://root:***@db/app', Password => '***') WHERE name = 'oops"
+    );
+}
@@ -0,0 +1,201 @@
+use app_lib::context::context::{collect_script, get_sql_context, register, SOURCE_TABLE_PREFIX};
+use app_lib::context::schema::AppResult;
+use app_lib::context::sources::MAX_SOURCE_TABLES;
+use datafusion::arrow::array::AsArray;
+use datafusion::arrow::datatypes::Int64Type;
+use datafusion::prelude::SessionContext;
+use std::fs;
+
+#[tokio::test]
+async fn test_register_nested_table_functions() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_register_test.csv");
+    fs::write(&path, "id,name\n1,a\n2,b\n3,c\n")?;
+    let path = path.display();
+
+    let sql = format!(
+        r#"
+SELECT id, (SELECT max(id) FROM read_csv('{path}')) AS max_id
+FROM read_csv('{path}')
+WHERE id IN (SELECT id FROM read_csv('{path}') WHERE name <> 'c')
+UNION ALL
+(SELECT t1.id, 0 FROM read_csv('{path}') t1
+ JOIN (read_csv('{path}') t2 JOIN read_csv('{path}') t3 ON t2.id = t3.id) ON t1.id = t2.id
+ LIMIT 0)
+    "#
+    );
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, None, None).await?;
+    assert!(!statements[0].contains("read_csv"));
+
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_script() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_register_script_test.csv");
+    fs::write(&path, "id,name\n1,a\n2,b\n3,c\n")?;
+    let path = path.display();
+
+    let sql = format!(
+        r#"
+CREATE VIEW a AS SELECT id FROM read_csv('{path}') WHERE id > 1;
+CREATE VIEW b AS SELECT id, name FROM read_csv('{path}');
+SELECT b.name FROM a JOIN b ON a.id = b.id
+    "#
+    );
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, Some(200), None).await?;
+    assert_eq!(statements.len(), 3);
+
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 2);
+
+    let script = ["SELECT 1".to_string(), "SELECT * FROM missing".to_string()];
+    let err = collect_script(&mut context, &script).await.unwrap_err();
+    assert!(err.to_string().contains("Statement 2"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_script_twice() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_register_twice_test.csv");
+    fs::write(&path, "id,name\n1,a\n2,b\n3,c\n")?;
+    let path = path.display();
+
+    let sql = format!(
+        r#"
+CREATE VIEW a AS SELECT id FROM read_csv('{path}') WHERE id > 1;
+CREATE TABLE b AS SELECT id, name FROM read_csv('{path}');
+SELECT b.name FROM a JOIN b ON a.id = b.id
+    "#
+    );
+
+    // Fetching a script again, or paging through it, replays the whole script
+    let mut context = get_sql_context();
+    for _ in 0..2 {
+        let statements = register(&mut context, &sql, Some(200), None).await?;
+        let records = collect_script(&mut context, &statements).await?;
+        assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 2);
+
+        let records = collect_script(&mut context, &statements).await?;
+        assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 2);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_replaces_changed_sources() -> AppResult<()> {
+    let dir = std::env::temp_dir().join("easydb_register_glob_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("a.csv"), "id\n1\n2\n")?;
+
+    let sql = format!(
+        "SELECT count(*) FROM read_csv('{}')",
+        dir.join("*.csv").display()
+    );
+    let mut context = get_sql_context();
+
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 2);
+    drop(statements);
+
+    // A new match of the glob is a new version of the source, replacing the old one
+    fs::write(dir.join("b.csv"), "id\n3\n")?;
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 3);
+    assert_eq!(count_source_tables(&context), 1);
+
+    Ok(())
+}
+
+/// One single-row CSV file per source, so every `read_csv` is a distinct source
+fn write_sources(name: &str, count: usize) -> AppResult<Vec<String>> {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+
+    let mut selects = Vec::with_capacity(count);
+    for index in 0..count {
+        let path = dir.join(format!("{}.csv", index));
+        fs::write(&path, format!("id\n{}\n", index))?;
+        selects.push(format!("SELECT id FROM read_csv('{}')", path.display()));
+    }
+    Ok(selects)
+}
+
+fn count_source_tables(context: &SessionContext) -> usize {
+    context
+        .catalog("datafusion")
+        .and_then(|catalog| catalog.schema("public"))
+        .map(|schema| schema.table_names())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| name.starts_with(SOURCE_TABLE_PREFIX))
+        .count()
+}
+
+#[tokio::test]
+async fn test_register_script_with_many_sources() -> AppResult<()> {
+    let count = MAX_SOURCE_TABLES + 8;
+    let selects = write_sources("easydb_register_many_test", count)?;
+
+    // Sources registered by the first statements must survive the later ones
+    let mut sql = selects
+        .iter()
+        .enumerate()
+        .map(|(index, select)| format!("CREATE VIEW v{} AS {}", index, select))
+        .collect::<Vec<_>>();
+    sql.push(format!(
+        "SELECT count(*) FROM ({})",
+        selects.join(" UNION ALL ")
+    ));
+    let sql = sql.join(";\n");
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(
+        records[0].column(0).as_primitive::<Int64Type>().value(0),
+        count as i64
+    );
+
+    // Unpinned once the script is dropped, and trimmed back to the limit
+    drop(statements);
+    assert!(count_source_tables(&context) <= MAX_SOURCE_TABLES);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_interleaved_scripts() -> AppResult<()> {
+    let selects = write_sources("easydb_register_interleaved_test", MAX_SOURCE_TABLES + 1)?;
+    let (first, others) = selects.split_first().unwrap();
+
+    let mut context = get_sql_context();
+    let first_statements = register(&mut context, first, None, None).await?;
+
+    // Another query registers enough sources to evict the first one's if it weren't in use
+    let mut other_context = context.clone();
+    let sql = others.join(" UNION ALL ");
+    let other_statements = register(&mut other_context, &sql, None, None).await?;
+    let records = collect_script(&mut other_context, &other_statements).await?;
+    assert_eq!(
+        records.iter().map(|r| r.num_rows()).sum::<usize>(),
+        MAX_SOURCE_TABLES
+    );
+
+    let records = collect_script(&mut context, &first_statements).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use app_lib::context::context::{collect_script, get_sql_context, register};
+use app_lib::context::schema::AppResult;
+use datafusion::arrow::array::AsArray;
+use datafusion::arrow::datatypes::Int64Type;
+use rusqlite::Connection;
+use std::fs;
+
+#[tokio::test]
+async fn test_read_sqlite() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_sqlite_test.db");
+    let _ = fs::remove_file(&path);
+
+    let conn = Connection::open(&path)?;
+    conn.execute_batch(
+        r#"
+CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT, amount REAL, created_at DATETIME);
+INSERT INTO orders VALUES (1, 'a', 10.5, '2024-01-01'), (2, 'b', 20, '2024-01-02'),
+                          (3, 'a', 30, '2024-01-03'), (4, NULL, 40, NULL);
+        "#,
+    )?;
+    drop(conn);
+
+    let sql = format!(
+        "SELECT id, amount FROM read_sqlite('{}', table => 'orders') WHERE customer = 'a' AND amount > 20 ORDER BY id",
+        path.display()
+    );
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records.iter().map(|r| r.num_rows()).sum::<usize>(), 1);
+
+    let sql = format!(
+        "SELECT count(*) FROM read_sqlite('{}', table => 'orders') WHERE customer IS NULL",
+        path.display()
+    );
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    let count = records[0].column(0).as_primitive::<Int64Type>().value(0);
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_sqlite_filters_rechecked() -> AppResult<()> {
+    let path = std::env::temp_dir().join("easydb_read_sqlite_collate_test.db");
+    let _ = fs::remove_file(&path);
+
+    let conn = Connection::open(&path)?;
+    conn.execute_batch(
+        r#"
+CREATE TABLE tags (name TEXT COLLATE NOCASE);
+INSERT INTO tags VALUES ('a'), ('A'), ('b');
+        "#,
+    )?;
+    drop(conn);
+
+    // SQLite matches both 'a' and 'A' here, DataFusion only the exact value
+    let sql = format!(
+        "SELECT count(*) FROM read_sqlite('{}', table => 'tags') WHERE name = 'a'",
+        path.display()
+    );
+
+    let mut context = get_sql_context();
+    let statements = register(&mut context, &sql, None, None).await?;
+    let records = collect_script(&mut context, &statements).await?;
+    assert_eq!(records[0].column(0).as_primitive::<Int64Type>().value(0), 1);
+
+    Ok(())
+}
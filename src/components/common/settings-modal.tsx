@@ -1,4 +1,4 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import {
   Select,
   SelectItem,
@@ -9,11 +9,14 @@ import {
   ModalHeader,
   ModalBody,
   ModalFooter,
+  Switch,
 } from "@heroui/react";
+import { invoke } from "@tauri-apps/api/core";
 import { useTranslation } from "@/i18n";
 import { useFontSize } from "../../hooks/useFontSize";
 import { useLanguage } from "../../hooks/useLanguage";
 import { open, ask } from "@tauri-apps/plugin-dialog";
+import ConnectionSettings from "./connection-settings";
 
 // 设置分类定义
 const SETTING_CATEGORIES = [
@@ -22,6 +25,11 @@ const SETTING_CATEGORIES = [
     label: "常规设置",
     icon: "⚙️",
   },
+  {
+    key: "connections",
+    label: "数据库连接",
+    icon: "🔌",
+  },
   {
     key: "authorization",
     label: "权限管理",
@@ -51,6 +59,24 @@ function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
   // 使用全局上下文
   const { fontSize, setFontSize } = useFontSize();
   const { language, setLanguage } = useLanguage();
+  const [historyEnabled, setHistoryEnabled] = useState(true);
+
+  useEffect(() => {
+    if (isOpen) {
+      invoke<boolean>("get_history_enabled")
+        .then(setHistoryEnabled)
+        .catch((error) => console.error("读取历史记录设置失败:", error));
+    }
+  }, [isOpen]);
+
+  const handleHistoryChange = async (enabled: boolean) => {
+    try {
+      await invoke("set_history_enabled", { enabled });
+      setHistoryEnabled(enabled);
+    } catch (error) {
+      console.error("保存历史记录设置失败:", error);
+    }
+  };
 
   const handleSettingChange = async (key: string, value: string | number) => {
     if (key === "fontSize") {
@@ -194,6 +220,19 @@ function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
             </div>
           </div>
         </div>
+
+        <div style={{ marginBottom: "20px" }}>
+          <Switch
+            size="sm"
+            isSelected={historyEnabled}
+            onValueChange={handleHistoryChange}
+          >
+            {translate("settings.recordHistory")}
+          </Switch>
+          <p style={{ fontSize: "12px", color: "#888", marginTop: "6px" }}>
+            {translate("settings.recordHistoryHint")}
+          </p>
+        </div>
       </div>
     </div>
   );
@@ -324,6 +363,8 @@ function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
     switch (selectedCategory) {
       case "general":
         return renderGeneralSettings();
+      case "connections":
+        return <ConnectionSettings />;
       case "authorization":
         return renderAuthorizationSettings();
       default:
@@ -374,6 +415,8 @@ function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
                         <span className="text-sm font-medium">
                           {category.key === "general"
                             ? translate("common.general")
+                            : category.key === "connections"
+                            ? translate("settings.connections.title")
                             : category.label}
                         </span>
                       </div>
@@ -57,8 +57,21 @@ export interface Translations {
       databaseDialectPlaceholder: string;
       mysql: string;
       postgresql: string;
+      sqlite: string;
+      sqlserver: string;
+      oracle: string;
+      clickhouse: string;
       cancel: string;
       confirmExport: string;
+      database: string;
+      databaseExportSettings: string;
+      databaseExportDescription: string;
+      connection: string;
+      connectionPlaceholder: string;
+      noConnections: string;
+      createTable: string;
+      truncate: string;
+      rowsWritten: string;
     };
   };
   functions: {
@@ -104,6 +117,12 @@ export interface Translations {
       description: string;
       inferSchema: string;
       sheetName: string;
+      batchSize: string;
+      range: string;
+      headerRow: string;
+      hasHeader: string;
+      skipRows: string;
+      skipFooter: string;
     };
     readParquet: {
       name: string;
@@ -114,6 +133,25 @@ export interface Translations {
       description: string;
       table: string;
       conn: string;
+      connName: string;
+      sslmode: string;
+      sslRootcert: string;
+      connectTimeout: string;
+    };
+    readSqlite: {
+      name: string;
+      description: string;
+      table: string;
+    };
+    readPostgres: {
+      name: string;
+      description: string;
+      conn: string;
+      connName: string;
+      sslmode: string;
+      sslRootcert: string;
+      poolSize: string;
+      connectTimeout: string;
     };
     regexpLike: {
       name: string;
@@ -128,6 +166,20 @@ export interface Translations {
     fontSize: string;
     displaySettings: string;
     generalSettings: string;
+    recordHistory: string;
+    recordHistoryHint: string;
+    connections: {
+      title: string;
+      description: string;
+      name: string;
+      conn: string;
+      save: string;
+      test: string;
+      delete: string;
+      testSuccess: string;
+      saved: string;
+      empty: string;
+    };
   };
   languages: {
     "zh-CN": string;
@@ -227,8 +279,22 @@ const translations: Record<Language, Translations> = {
         databaseDialectPlaceholder: "请选择数据库方言",
         mysql: "MySQL",
         postgresql: "PostgreSQL",
+        sqlite: "SQLite",
+        sqlserver: "SQL Server",
+        oracle: "Oracle",
+        clickhouse: "ClickHouse",
         cancel: "取消",
         confirmExport: "确认导出",
+        database: "写入 MySQL",
+        databaseExportSettings: "写入数据库",
+        databaseExportDescription:
+          "通过已保存的连接把查询结果写入 MySQL 表，失败时整体回滚",
+        connection: "连接",
+        connectionPlaceholder: "请选择已保存的连接",
+        noConnections: "请先在设置中保存 MySQL 连接",
+        createTable: "表不存在时自动创建",
+        truncate: "写入前清空表",
+        rowsWritten: "写入行数",
       },
     },
     functions: {
@@ -274,10 +340,17 @@ const translations: Record<Language, Translations> = {
       },
       readExcel: {
         name: "read_excel",
-        description: "读取 Excel 文件为表。",
+        description: "读取 Excel 文件为表，支持 xlsx、xls、xlsb 和 ods。",
         inferSchema:
           "是否自动推断数据类型。为 true 时，将根据前 100 行进行推断。",
-        sheetName: "要读取的工作表名称，默认读取第一个 sheet。",
+        sheetName:
+          "要读取的工作表名称，默认读取第一个 sheet。使用 '*' 或 '2024-*' 等通配符时合并所有匹配的工作表，并增加 __sheet 列。",
+        batchSize: "每批读取的行数，默认 8192。",
+        range: "只读取该单元格区域，例如 'B3:H200'。",
+        headerRow: "表头所在的行号（从 1 开始，指定 range 时从区域第一行算起），之前的行会被跳过。",
+        hasHeader: "第一行是否为表头。为 false 时列名为 t1..tn。",
+        skipRows: "表头之后、数据之前要跳过的行数。",
+        skipFooter: "忽略末尾的行数，例如合计行。",
       },
       readParquet: {
         name: "read_parquet",
@@ -287,7 +360,26 @@ const translations: Record<Language, Translations> = {
         name: "read_mysql",
         description: "从 MySQL 数据库读取表数据。",
         table: "要读取的表名称。",
-        conn: "MySQL 连接字符串",
+        conn: "MySQL 连接字符串，或用 @名称 引用已保存的连接",
+        connName: "已保存连接的名称",
+        sslmode: "SSL 模式：disabled、preferred 或 required",
+        sslRootcert: "用于校验服务端证书的 CA 证书路径",
+        connectTimeout: "连接超时时间（秒）",
+      },
+      readSqlite: {
+        name: "read_sqlite",
+        description: "读取本地 SQLite 数据库文件中的表。",
+        table: "要读取的表名称。",
+      },
+      readPostgres: {
+        name: "read_postgres",
+        description: "从 PostgreSQL 数据库读取表数据。",
+        conn: "PostgreSQL 连接字符串，或用 @名称 引用已保存的连接",
+        connName: "已保存连接的名称",
+        sslmode: "SSL 模式：disable、prefer、require、verify-ca 或 verify-full",
+        sslRootcert: "用于校验服务端证书的 CA 证书路径",
+        poolSize: "连接池大小",
+        connectTimeout: "连接超时时间（秒）",
       },
       regexpLike: {
         name: "REGEXP_LIKE",
@@ -302,6 +394,21 @@ const translations: Record<Language, Translations> = {
       fontSize: "字体大小",
       displaySettings: "显示设置",
       generalSettings: "常规设置",
+      recordHistory: "记录查询历史",
+      recordHistoryHint: "保存前会隐藏 SQL 中连接字符串的密码和 password、api_key 等参数的值",
+      connections: {
+        title: "数据库连接",
+        description:
+          "连接字符串保存在系统钥匙串中，在 SQL 中通过 conn => @名称 引用，无需写入密码。",
+        name: "名称",
+        conn: "连接字符串",
+        save: "保存",
+        test: "测试连接",
+        delete: "删除",
+        testSuccess: "连接成功",
+        saved: "连接已保存",
+        empty: "暂无已保存的连接",
+      },
     },
     languages: {
       "zh-CN": "简体中文",
@@ -407,8 +514,22 @@ const translations: Record<Language, Translations> = {
         databaseDialectPlaceholder: "Please select database dialect",
         mysql: "MySQL",
         postgresql: "PostgreSQL",
+        sqlite: "SQLite",
+        sqlserver: "SQL Server",
+        oracle: "Oracle",
+        clickhouse: "ClickHouse",
         cancel: "Cancel",
         confirmExport: "Confirm Export",
+        database: "Write to MySQL",
+        databaseExportSettings: "Write to Database",
+        databaseExportDescription:
+          "Load the result into a MySQL table through a saved connection, rolled back as a whole on failure",
+        connection: "Connection",
+        connectionPlaceholder: "Select a saved connection",
+        noConnections: "Save a MySQL connection in Settings first",
+        createTable: "Create the table if it doesn't exist",
+        truncate: "Empty the table first",
+        rowsWritten: "Rows written",
       },
     },
     functions: {
@@ -456,10 +577,18 @@ const translations: Record<Language, Translations> = {
       },
       readExcel: {
         name: "read_excel",
-        description: "Read Excel file as table.",
+        description: "Read Excel file as table. Supports xlsx, xls, xlsb and ods.",
         inferSchema:
           "Whether to automatically infer data types. If true, the first 100 rows are used for inference.",
-        sheetName: "Name of the sheet to read, defaults first sheet.",
+        sheetName:
+          "Name of the sheet to read, defaults first sheet. A wildcard such as '*' or '2024-*' unions all matching sheets and adds a __sheet column.",
+        batchSize: "Number of rows read per batch, defaults to 8192.",
+        range: "Only read this block of cells, e.g. 'B3:H200'.",
+        headerRow:
+          "Row number of the header (1-based, counted from the first row of range when given). Rows above it are skipped.",
+        hasHeader: "Whether the first row is a header. If false, columns are named t1..tn.",
+        skipRows: "Number of rows to skip between the header and the data.",
+        skipFooter: "Number of rows to ignore at the end, e.g. totals.",
       },
       readParquet: {
         name: "read_parquet",
@@ -469,7 +598,26 @@ const translations: Record<Language, Translations> = {
         name: "read_mysql",
         description: "Read table data from MySQL database.",
         table: "Name of the table to read.",
-        conn: "MySQL connection string.",
+        conn: "MySQL connection string, or @name of a saved connection.",
+        connName: "Name of a saved connection.",
+        sslmode: "SSL mode: disabled, preferred or required.",
+        sslRootcert: "Path of the CA certificate used to verify the server.",
+        connectTimeout: "Connection timeout in seconds.",
+      },
+      readSqlite: {
+        name: "read_sqlite",
+        description: "Read a table from a local SQLite database file.",
+        table: "Name of the table to read.",
+      },
+      readPostgres: {
+        name: "read_postgres",
+        description: "Read table data from PostgreSQL database.",
+        conn: "PostgreSQL connection string, or @name of a saved connection.",
+        connName: "Name of a saved connection.",
+        sslmode: "SSL mode: disable, prefer, require, verify-ca or verify-full.",
+        sslRootcert: "Path of the CA certificate used to verify the server.",
+        poolSize: "Connection pool size.",
+        connectTimeout: "Connection timeout in seconds.",
       },
       regexpLike: {
         name: "REGEXP_LIKE",
@@ -484,6 +632,22 @@ const translations: Record<Language, Translations> = {
       fontSize: "Font Size",
       displaySettings: "Display Settings",
       generalSettings: "General Settings",
+      recordHistory: "Record query history",
+      recordHistoryHint:
+        "Passwords in connection strings and values of arguments such as password or api_key are masked before saving",
+      connections: {
+        title: "Database Connections",
+        description:
+          "Connection strings are kept in the system keychain. Reference them in SQL with conn => @name instead of writing the password.",
+        name: "Name",
+        conn: "Connection string",
+        save: "Save",
+        test: "Test connection",
+        delete: "Delete",
+        testSuccess: "Connection succeeded",
+        saved: "Connection saved",
+        empty: "No saved connections",
+      },
     },
     languages: {
       "zh-CN": "简体中文",
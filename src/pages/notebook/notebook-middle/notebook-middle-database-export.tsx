@@ -0,0 +1,177 @@
+import {
+  Button,
+  Input,
+  Modal,
+  ModalBody,
+  ModalContent,
+  ModalFooter,
+  ModalHeader,
+  Select,
+  SelectItem,
+  Switch,
+} from "@heroui/react";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { useTranslation } from "../../../i18n";
+
+interface SavedConnection {
+  name: string;
+  kind: string;
+  conn: string;
+}
+
+export interface DatabaseExportResult {
+  query_time: string;
+  rows_written: number;
+}
+
+interface DatabaseExportProps {
+  isOpen: boolean;
+  onOpenChange: (isOpen: boolean) => void;
+  onExport: (options: {
+    connection: string;
+    tableName: string;
+    batchSize: number;
+    createTable: boolean;
+    truncate: boolean;
+  }) => void;
+}
+
+function DatabaseExport({ isOpen, onOpenChange, onExport }: DatabaseExportProps) {
+  const { translate } = useTranslation();
+  const [connections, setConnections] = useState<SavedConnection[]>([]);
+  const [connection, setConnection] = useState("");
+  const [tableName, setTableName] = useState("table_name");
+  const [batchSize, setBatchSize] = useState(1000);
+  const [createTable, setCreateTable] = useState(true);
+  const [truncate, setTruncate] = useState(false);
+
+  useEffect(() => {
+    if (!isOpen) {
+      return;
+    }
+    invoke<SavedConnection[]>("list_connections")
+      .then((saved) =>
+        setConnections(saved.filter((item) => item.kind === "mysql"))
+      )
+      .catch((error) => console.error("Failed to load connections:", error));
+  }, [isOpen]);
+
+  return (
+    <Modal
+      isOpen={isOpen}
+      onOpenChange={onOpenChange}
+      placement="center"
+      size="lg"
+      classNames={{
+        base: "bg-background",
+        backdrop: "bg-black/50",
+      }}
+    >
+      <ModalContent>
+        {(onClose) => (
+          <>
+            <ModalHeader className="flex flex-col gap-2 pb-4">
+              <h2 className="text-xl font-semibold text-foreground">
+                {translate("notebook.export.databaseExportSettings")}
+              </h2>
+              <p className="text-sm text-default-500">
+                {translate("notebook.export.databaseExportDescription")}
+              </p>
+            </ModalHeader>
+
+            <ModalBody className="gap-6 py-6">
+              <Select
+                label={translate("notebook.export.connection")}
+                placeholder={
+                  connections.length === 0
+                    ? translate("notebook.export.noConnections")
+                    : translate("notebook.export.connectionPlaceholder")
+                }
+                selectedKeys={connection ? [connection] : []}
+                onSelectionChange={(keys) =>
+                  setConnection((Array.from(keys)[0] as string) ?? "")
+                }
+                isDisabled={connections.length === 0}
+                variant="bordered"
+              >
+                {connections.map((item) => (
+                  <SelectItem key={item.name} textValue={item.name}>
+                    <div className="flex flex-col">
+                      <span>@{item.name}</span>
+                      <span className="text-xs text-default-400">
+                        {item.conn}
+                      </span>
+                    </div>
+                  </SelectItem>
+                ))}
+              </Select>
+
+              <Input
+                label={translate("notebook.export.tableName")}
+                placeholder={translate("notebook.export.tableNamePlaceholder")}
+                value={tableName}
+                onValueChange={setTableName}
+                variant="bordered"
+              />
+
+              <Input
+                type="number"
+                label={translate("notebook.export.maxValuesPerInsert")}
+                value={batchSize.toString()}
+                onValueChange={(value) => setBatchSize(parseInt(value) || 0)}
+                min={1}
+                variant="bordered"
+              />
+
+              <div className="flex flex-col gap-3">
+                <Switch
+                  size="sm"
+                  isSelected={createTable}
+                  onValueChange={setCreateTable}
+                >
+                  {translate("notebook.export.createTable")}
+                </Switch>
+                <Switch size="sm" isSelected={truncate} onValueChange={setTruncate}>
+                  {translate("notebook.export.truncate")}
+                </Switch>
+              </div>
+            </ModalBody>
+
+            <ModalFooter className="gap-3 pt-4">
+              <Button
+                color="default"
+                variant="light"
+                onPress={onClose}
+                size="lg"
+                className="font-medium"
+              >
+                {translate("notebook.export.cancel")}
+              </Button>
+              <Button
+                color="primary"
+                onPress={() => {
+                  onExport({
+                    connection,
+                    tableName: tableName.trim(),
+                    batchSize,
+                    createTable,
+                    truncate,
+                  });
+                  onClose();
+                }}
+                isDisabled={!connection || !tableName.trim() || batchSize < 1}
+                size="lg"
+                className="font-medium"
+              >
+                {translate("notebook.export.confirmExport")}
+              </Button>
+            </ModalFooter>
+          </>
+        )}
+      </ModalContent>
+    </Modal>
+  );
+}
+
+export default DatabaseExport;
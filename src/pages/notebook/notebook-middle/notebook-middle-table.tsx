@@ -5,6 +5,7 @@ import {
   faCheckCircle,
   faTimes,
   faFileCode,
+  faDatabase,
 } from "@fortawesome/free-solid-svg-icons";
 import { FontAwesomeIcon } from "@fortawesome/react-fontawesome";
 import {
@@ -26,6 +27,9 @@ import {
 } from "@heroui/react";
 import { memo, useState, useEffect } from "react";
 import DataResult from "./notebook-middle-data-result";
+import DatabaseExport, {
+  DatabaseExportResult,
+} from "./notebook-middle-database-export";
 import { invoke } from "@tauri-apps/api/core";
 import { useTranslation } from "../../../i18n";
 
@@ -40,7 +44,8 @@ interface TableProps {
 
 interface ExportResult {
   query_time: string;
-  file_name: string;
+  file_name?: string;
+  rows_written?: number;
 }
 
 function DataTable({ data, isLoading, sql }: TableProps) {
@@ -52,6 +57,7 @@ function DataTable({ data, isLoading, sql }: TableProps) {
   const [sqlStatementType, setSqlStatementType] = useState("INSERT");
   const [whereColumn, setWhereColumn] = useState("");
   const [databaseDialect, setDatabaseDialect] = useState("MySQL");
+  const [isDatabaseModalOpen, setIsDatabaseModalOpen] = useState(false);
   const { translate } = useTranslation();
 
   // 自动隐藏提示
@@ -76,14 +82,43 @@ function DataTable({ data, isLoading, sql }: TableProps) {
       setIsDownloading(true);
       setExportResult(null);
 
+      // Each format only accepts its own options
       const result = await invoke<ExportResult>("writer", {
         sql,
         fileType,
-        tableName: fileType === "SQL" ? tableName : undefined,
-        maxValuesPerInsert: fileType === "SQL" ? maxValuesPerInsert : undefined,
-        sqlStatementType: fileType === "SQL" ? sqlStatementType : undefined,
-        whereColumn: fileType === "SQL" ? whereColumn : undefined,
-        dialect: fileType === "SQL" ? dialect : undefined,
+        options:
+          fileType === "SQL"
+            ? {
+                tableName,
+                maxValuesPerInsert,
+                statementType: sqlStatementType,
+                whereColumn,
+                dialect,
+              }
+            : undefined,
+      });
+      setExportResult(result);
+    } catch (error) {
+      console.error("Export failed:", error);
+    } finally {
+      setIsDownloading(false);
+    }
+  }
+
+  async function exportToDatabase(options: {
+    connection: string;
+    tableName: string;
+    batchSize: number;
+    createTable: boolean;
+    truncate: boolean;
+  }) {
+    try {
+      setIsDownloading(true);
+      setExportResult(null);
+
+      const result = await invoke<DatabaseExportResult>("export_to_database", {
+        sql,
+        ...options,
       });
       setExportResult(result);
     } catch (error) {
@@ -157,10 +192,17 @@ function DataTable({ data, isLoading, sql }: TableProps) {
               {translate("notebook.export.queryTime")}:{" "}
               {exportResult.query_time}
             </div>
-            <div style={{ fontSize: "12px", opacity: 0.8 }}>
-              {translate("notebook.export.fileName")}:{" "}
-              {exportResult.file_name.split("/").pop()}
-            </div>
+            {exportResult.file_name !== undefined ? (
+              <div style={{ fontSize: "12px", opacity: 0.8 }}>
+                {translate("notebook.export.fileName")}:{" "}
+                {exportResult.file_name.split("/").pop()}
+              </div>
+            ) : (
+              <div style={{ fontSize: "12px", opacity: 0.8 }}>
+                {translate("notebook.export.rowsWritten")}:{" "}
+                {exportResult.rows_written}
+              </div>
+            )}
           </div>
           <button
             onClick={() => setExportResult(null)}
@@ -238,12 +280,28 @@ function DataTable({ data, isLoading, sql }: TableProps) {
               />
               SQL
             </DropdownItem>
+            <DropdownItem
+              key="database"
+              onPress={() => setIsDatabaseModalOpen(true)}
+            >
+              <FontAwesomeIcon
+                icon={faDatabase}
+                style={{ marginRight: "5px" }}
+              />
+              {translate("notebook.export.database")}
+            </DropdownItem>
           </DropdownMenu>
         </Dropdown>
       </div>
 
       <DataResult data={data} isLoading={isLoading} />
 
+      <DatabaseExport
+        isOpen={isDatabaseModalOpen}
+        onOpenChange={setIsDatabaseModalOpen}
+        onExport={exportToDatabase}
+      />
+
       {/* 表名输入模态框 */}
       <Modal
         isOpen={isTableNameModalOpen}
@@ -291,13 +349,15 @@ function DataTable({ data, isLoading, sql }: TableProps) {
                       tabList:
                         "gap-0 w-full relative rounded-xl p-1 bg-default-100",
                       cursor: "w-full bg-background shadow-md rounded-lg",
-                      tab: "flex-1 px-6 h-12 min-w-0 w-1/2 font-medium",
+                      tab: "flex-1 px-6 h-12 min-w-0 w-1/4 font-medium",
                       tabContent:
                         "group-data-[selected=true]:text-primary text-center font-semibold",
                     }}
                   >
                     <Tab key="INSERT" title="INSERT" />
                     <Tab key="UPDATE" title="UPDATE" />
+                    <Tab key="UPSERT" title="UPSERT" />
+                    <Tab key="DELETE" title="DELETE" />
                   </Tabs>
                 </div>
 
@@ -342,7 +402,7 @@ function DataTable({ data, isLoading, sql }: TableProps) {
                     size="lg"
                     variant="bordered"
                     isDisabled={sqlStatementType === "INSERT"}
-                    isRequired={sqlStatementType === "UPDATE"}
+                    isRequired={sqlStatementType !== "INSERT"}
                     classNames={{
                       trigger:
                         "text-base border-default-200 hover:border-primary-300 focus-within:border-primary-500",
@@ -424,6 +484,18 @@ function DataTable({ data, isLoading, sql }: TableProps) {
                     <SelectItem key="PostgreSQL" className="text-base">
                       {translate("notebook.export.postgresql")}
                     </SelectItem>
+                    <SelectItem key="SQLite" className="text-base">
+                      {translate("notebook.export.sqlite")}
+                    </SelectItem>
+                    <SelectItem key="SQLServer" className="text-base">
+                      {translate("notebook.export.sqlserver")}
+                    </SelectItem>
+                    <SelectItem key="Oracle" className="text-base">
+                      {translate("notebook.export.oracle")}
+                    </SelectItem>
+                    <SelectItem key="ClickHouse" className="text-base">
+                      {translate("notebook.export.clickhouse")}
+                    </SelectItem>
                   </Select>
                 </div>
               </ModalBody>
@@ -447,7 +519,7 @@ function DataTable({ data, isLoading, sql }: TableProps) {
                   isDisabled={
                     !tableName.trim() ||
                     (sqlStatementType === "INSERT" && maxValuesPerInsert < 1) ||
-                    (sqlStatementType === "UPDATE" && !whereColumn.trim())
+                    (sqlStatementType !== "INSERT" && !whereColumn.trim())
                   }
                   size="lg"
                   className="font-medium"
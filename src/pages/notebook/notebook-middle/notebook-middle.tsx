@@ -193,6 +193,10 @@ function NotebookMiddle({
         `SELECT * FROM read_excel('${filePath}') LIMIT 100;`,
       xls: (filePath: string) =>
         `SELECT * FROM read_excel('${filePath}') LIMIT 100;`,
+      xlsb: (filePath: string) =>
+        `SELECT * FROM read_excel('${filePath}') LIMIT 100;`,
+      ods: (filePath: string) =>
+        `SELECT * FROM read_excel('${filePath}') LIMIT 100;`,
       json: (filePath: string) =>
         `SELECT * FROM read_ndjson('${filePath}') LIMIT 100;`,
       ndjson: (filePath: string) =>
@@ -213,6 +217,8 @@ function NotebookMiddle({
       csv: "read_csv",
       xlsx: "read_excel",
       xls: "read_excel",
+      xlsb: "read_excel",
+      ods: "read_excel",
       json: "read_ndjson",
       ndjson: "read_ndjson",
       parquet: "read_parquet",
@@ -488,6 +494,8 @@ function NotebookMiddle({
               "tsv",
               "xlsx",
               "xls",
+              "xlsb",
+              "ods",
               "json",
               "ndjson",
               "parquet",